@@ -0,0 +1,94 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lz_str::Encoding;
+
+const NUM_PAYLOADS: usize = 10_000;
+
+/// Make small payloads, like a column of JSON values.
+fn payloads() -> Vec<String> {
+    (0..NUM_PAYLOADS)
+        .map(|i| {
+            format!(
+                "{{\"id\":{i},\"name\":\"user-{i}\",\"tags\":[\"a\",\"b\",\"c\"],\"score\":{}}}",
+                i * 7 % 1000
+            )
+        })
+        .collect()
+}
+
+/// The thread counts to measure scaling with.
+fn thread_counts() -> Vec<usize> {
+    let max = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut counts: Vec<usize> = [1, 2, 4, 8].iter().copied().filter(|&n| n < max).collect();
+    counts.push(max);
+    counts
+}
+
+pub fn bench(c: &mut Criterion) {
+    let payloads = payloads();
+    let compressed: Vec<String> = payloads.iter().map(lz_str::compress_to_base64).collect();
+
+    let mut group = c.benchmark_group("decompress_batch");
+    group.throughput(Throughput::Elements(NUM_PAYLOADS as u64));
+    group.bench_with_input(
+        BenchmarkId::new("decompress_from_base64", "sequential"),
+        &compressed,
+        |b, compressed| {
+            b.iter(|| {
+                compressed
+                    .iter()
+                    .map(|s| lz_str::decompress_from_base64(s))
+                    .collect::<Vec<_>>()
+            });
+        },
+    );
+    for num_threads in thread_counts() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("decompress_batch", num_threads),
+            &compressed,
+            |b, compressed| {
+                b.iter(|| pool.install(|| lz_str::decompress_batch(compressed, Encoding::Base64)));
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("compress_batch");
+    group.throughput(Throughput::Elements(NUM_PAYLOADS as u64));
+    group.bench_with_input(
+        BenchmarkId::new("compress_to_base64", "sequential"),
+        &payloads,
+        |b, payloads| {
+            b.iter(|| {
+                payloads
+                    .iter()
+                    .map(lz_str::compress_to_base64)
+                    .collect::<Vec<_>>()
+            });
+        },
+    );
+    for num_threads in thread_counts() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("compress_batch", num_threads),
+            &payloads,
+            |b, payloads| {
+                b.iter(|| pool.install(|| lz_str::compress_batch(payloads, Encoding::Base64)));
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = bench
+}
+criterion_main!(benches);
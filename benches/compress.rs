@@ -2,6 +2,47 @@ use criterion::criterion_group;
 use criterion::criterion_main;
 use criterion::BenchmarkId;
 use criterion::Criterion;
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+/// An allocator that counts allocations, to compare how many each encoder makes.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// A compress function for an encoding, which drops its output.
+type Encoder = fn(&[u16]);
+
+/// Count the allocations and reallocations made by a function.
+fn count_allocations<R>(f: impl FnOnce() -> R) -> usize {
+    let start = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = f();
+    let count = ALLOCATIONS.load(Ordering::Relaxed) - start;
+    drop(result);
+    count
+}
 
 const TEST_PHRASE: &str = "During tattooing, ink is injected into the skin, initiating an immune response, and cells called \"macrophages\" move into the area and \"eat up\" the ink. The macrophages carry some of the ink to the body\'s lymph nodes, but some that are filled with ink stay put, embedded in the skin. That\'s what makes the tattoo visible under the skin. Dalhousie Uiversity\'s Alec Falkenham is developing a topical cream that works by targeting the macrophages that have remained at the site of the tattoo. New macrophages move in to consume the previously pigment-filled macrophages and then migrate to the lymph nodes, eventually taking all the dye with them. \"When comparing it to laser-based tattoo removal, in which you see the burns, the scarring, the blisters, in this case, we\'ve designed a drug that doesn\'t really have much off-target effect,\" he said. \"We\'re not targeting any of the normal skin cells, so you won\'t see a lot of inflammation. In fact, based on the process that we\'re actually using, we don\'t think there will be any inflammation at all and it would actually be anti-inflammatory.";
 
@@ -15,6 +56,31 @@ pub fn bench(c: &mut Criterion) {
             b.iter(|| lz_str::compress(s));
         },
     );
+
+    let encoders: [(&str, Encoder); 4] = [
+        ("compress_to_utf16", |s| drop(lz_str::compress_to_utf16(s))),
+        ("compress_to_encoded_uri_component", |s| {
+            drop(lz_str::compress_to_encoded_uri_component(s))
+        }),
+        ("compress_to_base64", |s| {
+            drop(lz_str::compress_to_base64(s))
+        }),
+        ("compress_to_uint8_array", |s| {
+            drop(lz_str::compress_to_uint8_array(s))
+        }),
+    ];
+    for (name, encoder) in encoders.iter() {
+        let allocations = count_allocations(|| encoder(&test_phrase));
+        println!("{name}: {allocations} allocations");
+
+        c.bench_with_input(
+            BenchmarkId::new(*name, "Tattoo Description"),
+            &test_phrase,
+            |b, s| {
+                b.iter(|| encoder(s));
+            },
+        );
+    }
 }
 
 criterion_group! {
@@ -3,7 +3,7 @@ use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 const TEST_PHRASE: &str = "During tattooing, ink is injected into the skin, initiating an immune response, and cells called \"macrophages\" move into the area and \"eat up\" the ink. The macrophages carry some of the ink to the body\'s lymph nodes, but some that are filled with ink stay put, embedded in the skin. That\'s what makes the tattoo visible under the skin. Dalhousie Uiversity\'s Alec Falkenham is developing a topical cream that works by targeting the macrophages that have remained at the site of the tattoo. New macrophages move in to consume the previously pigment-filled macrophages and then migrate to the lymph nodes, eventually taking all the dye with them. \"When comparing it to laser-based tattoo removal, in which you see the burns, the scarring, the blisters, in this case, we\'ve designed a drug that doesn\'t really have much off-target effect,\" he said. \"We\'re not targeting any of the normal skin cells, so you won\'t see a lot of inflammation. In fact, based on the process that we\'re actually using, we don\'t think there will be any inflammation at all and it would actually be anti-inflammatory.";
 
 pub fn bench(c: &mut Criterion) {
-    let compressed = lz_str::compress(&TEST_PHRASE.encode_utf16().collect::<Vec<u16>>());
+    let compressed = lz_str::compress(TEST_PHRASE.encode_utf16().collect::<Vec<u16>>());
 
     c.bench_with_input(
         BenchmarkId::new("decompress", "Tattoo Description"),
@@ -12,6 +12,41 @@ pub fn bench(c: &mut Criterion) {
             b.iter(|| lz_str::decompress(s));
         },
     );
+
+    // 100,000 mostly unique chars, so the output is dominated by decoding the input.
+    let compressed: Vec<u16> = include_str!("../test_data/long_compressed_js.txt")
+        .split(',')
+        .map(|s| s.trim().parse::<u16>().unwrap())
+        .collect();
+    let data = lz_str::decompress(&compressed).unwrap();
+
+    let mut group = c.benchmark_group("long_compressed_js");
+    group.sample_size(50);
+    let base64 = lz_str::compress_to_base64(&data);
+    group.bench_with_input(
+        BenchmarkId::new("decompress_from_base64", data.len()),
+        &base64,
+        |b, s| {
+            b.iter(|| lz_str::decompress_from_base64(s));
+        },
+    );
+    let uri = lz_str::compress_to_encoded_uri_component(&data);
+    group.bench_with_input(
+        BenchmarkId::new("decompress_from_encoded_uri_component", data.len()),
+        &uri,
+        |b, s| {
+            b.iter(|| lz_str::decompress_from_encoded_uri_component(s));
+        },
+    );
+    let uint8_array = lz_str::compress_to_uint8_array(&data);
+    group.bench_with_input(
+        BenchmarkId::new("decompress_from_uint8_array", data.len()),
+        &uint8_array,
+        |b, s| {
+            b.iter(|| lz_str::decompress_from_uint8_array(s));
+        },
+    );
+    group.finish();
 }
 
 criterion_group! {
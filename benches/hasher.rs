@@ -0,0 +1,34 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lz_str::{DictionaryHasher, Encoding};
+
+const HASHERS: [DictionaryHasher; 3] = [
+    DictionaryHasher::SipHash,
+    DictionaryHasher::FxHash,
+    DictionaryHasher::AHash,
+];
+
+pub fn bench(c: &mut Criterion) {
+    // Large enough that the dictionary, and not the small path, does the work.
+    let compressed: Vec<u16> = include_str!("../test_data/long_compressed_js.txt")
+        .split(',')
+        .map(|s| s.trim().parse::<u16>().unwrap())
+        .collect();
+    let data = lz_str::decompress(&compressed).unwrap();
+
+    let mut group = c.benchmark_group("compress_with_hasher");
+    group.sample_size(50);
+    group.throughput(Throughput::Bytes(data.len() as u64 * 2));
+    for hasher in HASHERS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{hasher:?}")),
+            &data,
+            |b, data| {
+                b.iter(|| lz_str::compress_with_hasher(data, Encoding::Base64, hasher));
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
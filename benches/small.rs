@@ -0,0 +1,94 @@
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use criterion::Throughput;
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+/// An allocator that counts allocations, to check which calls avoid them.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Count the allocations and reallocations made by a function.
+fn count_allocations<R>(f: impl FnOnce() -> R) -> usize {
+    let start = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = f();
+    let count = ALLOCATIONS.load(Ordering::Relaxed) - start;
+    drop(result);
+    count
+}
+
+const TEST_PHRASE: &str = "During tattooing, ink is injected into the skin, initiating an immune response, and cells called \"macrophages\" move into the area and \"eat up\" the ink. The macrophages carry some of the ink to the body\'s lymph nodes, but some that are filled with ink stay put, embedded in the skin. That\'s what makes the tattoo visible under the skin. Dalhousie Uiversity\'s Alec Falkenham is developing a topical cream that works by targeting the macrophages that have remained at the site of the tattoo. New macrophages move in to consume the previously pigment-filled macrophages and then migrate to the lymph nodes, eventually taking all the dye with them. \"When comparing it to laser-based tattoo removal, in which you see the burns, the scarring, the blisters, in this case, we\'ve designed a drug that doesn\'t really have much off-target effect,\" he said. \"We\'re not targeting any of the normal skin cells, so you won\'t see a lot of inflammation. In fact, based on the process that we\'re actually using, we don\'t think there will be any inflammation at all and it would actually be anti-inflammatory.";
+
+/// The input sizes to measure, in bytes.
+const SIZES: [usize; 5] = [16, 64, 256, 512, 1024];
+
+pub fn bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("small");
+    for &size in SIZES.iter() {
+        let input = &TEST_PHRASE[..size];
+        let compressed = lz_str::compress(input);
+        let base64 = lz_str::compress_to_base64(input);
+        println!(
+            "{size} bytes: compress {}, compress_to_base64 {}, decompress {}, decompress_from_base64 {} allocations",
+            count_allocations(|| lz_str::compress(input)),
+            count_allocations(|| lz_str::compress_to_base64(input)),
+            count_allocations(|| lz_str::decompress(&compressed)),
+            count_allocations(|| lz_str::decompress_from_base64(&base64)),
+        );
+
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("compress", size), input, |b, s| {
+            b.iter(|| lz_str::compress(s));
+        });
+        group.bench_with_input(
+            BenchmarkId::new("compress_to_base64", size),
+            input,
+            |b, s| {
+                b.iter(|| lz_str::compress_to_base64(s));
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("decompress", size), &compressed, |b, s| {
+            b.iter(|| lz_str::decompress(s));
+        });
+        group.bench_with_input(
+            BenchmarkId::new("decompress_from_base64", size),
+            base64.as_str(),
+            |b, s| {
+                b.iter(|| lz_str::decompress_from_base64(s));
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(200);
+    targets = bench
+}
+criterion_main!(benches);
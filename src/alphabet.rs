@@ -0,0 +1,222 @@
+//! Bulk mapping between 6-bit values and the ASCII alphabets of
+//! [`Encoding::EncodedUriComponent`] and [`Encoding::Base64`].
+//!
+//! With the `simd` feature, x86_64 CPUs that support AVX2 map 32 bytes at a time.
+//! Everything else uses the lookup tables in [`crate::constants`].
+
+use crate::constants::BASE64_KEY;
+use crate::constants::BASE64_VALUES;
+use crate::constants::NO_VALUE;
+use crate::constants::URI_KEY;
+use crate::constants::URI_VALUES;
+use crate::encoding::Encoding;
+
+/// The number of bytes an [`AsciiValues`] decodes at a time.
+const BLOCK_LEN: usize = 256;
+
+/// Whether the CPU can map chars with SIMD.
+///
+/// Mapping values to chars separately is only faster than doing it as they are written with SIMD.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[inline]
+pub(crate) fn has_simd() -> bool {
+    std::is_x86_feature_detected!("avx2")
+}
+
+/// Whether the CPU can map chars with SIMD.
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+#[inline]
+pub(crate) fn has_simd() -> bool {
+    false
+}
+
+/// Map 6-bit values to the chars of the alphabet of `encoding`, in place.
+///
+/// # Panics
+/// Panics if the encoding does not have an ASCII alphabet, or if a value is not less than 64.
+pub(crate) fn values_to_ascii(values: &mut [u8], encoding: Encoding) {
+    let key = key(encoding);
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    let values = if std::is_x86_feature_detected!("avx2") {
+        crate::avx2::values_to_ascii(values, key)
+    } else {
+        values
+    };
+
+    values_to_ascii_scalar(values, key);
+}
+
+/// Map the bytes of the alphabet of `encoding` to their values, skipping other bytes.
+///
+/// Returns the number of values written to the start of `output`,
+/// which must be at least as long as `input`.
+///
+/// # Panics
+/// Panics if the encoding does not have an ASCII alphabet, or if `output` is too short.
+pub(crate) fn ascii_to_values(input: &[u8], output: &mut [u8], encoding: Encoding) -> usize {
+    assert!(output.len() >= input.len());
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if std::is_x86_feature_detected!("avx2") {
+        return crate::avx2::ascii_to_values(input, output, encoding);
+    }
+
+    ascii_to_values_scalar(input, output, encoding)
+}
+
+/// The 64 chars of the alphabet of `encoding`.
+fn key(encoding: Encoding) -> &'static [u8] {
+    match encoding {
+        Encoding::EncodedUriComponent => &URI_KEY[..64],
+        Encoding::Base64 => &BASE64_KEY[..64],
+        Encoding::Raw | Encoding::Utf16 => {
+            panic!("{:?} does not have an ASCII alphabet", encoding)
+        }
+    }
+}
+
+fn values_to_ascii_scalar(values: &mut [u8], key: &[u8]) {
+    for value in values {
+        *value = key[usize::from(*value)];
+    }
+}
+
+pub(crate) fn ascii_to_values_scalar(input: &[u8], output: &mut [u8], encoding: Encoding) -> usize {
+    let values = match encoding {
+        Encoding::EncodedUriComponent => &URI_VALUES,
+        Encoding::Base64 => &BASE64_VALUES,
+        Encoding::Raw | Encoding::Utf16 => unreachable!(),
+    };
+
+    // Every value is written, but only valid ones are kept.
+    let mut len = 0;
+    for &b in input {
+        let value = values[usize::from(b)];
+        output[len] = value;
+        len += usize::from(value != NO_VALUE);
+    }
+    len
+}
+
+/// An iterator over the values of ASCII input, which decodes it in blocks.
+#[derive(Debug)]
+pub(crate) struct AsciiValues<'a> {
+    input: &'a [u8],
+    encoding: Encoding,
+
+    /// The values of the last decoded block.
+    buffer: [u8; BLOCK_LEN],
+    position: usize,
+    len: usize,
+}
+
+impl<'a> AsciiValues<'a> {
+    /// Make a new [`AsciiValues`].
+    ///
+    /// # Panics
+    /// Panics if the encoding does not have an ASCII alphabet.
+    pub(crate) fn new(input: &'a [u8], encoding: Encoding) -> Self {
+        assert!(
+            matches!(encoding, Encoding::EncodedUriComponent | Encoding::Base64),
+            "{:?} does not have an ASCII alphabet",
+            encoding
+        );
+
+        Self {
+            input,
+            encoding,
+            buffer: [NO_VALUE; BLOCK_LEN],
+            position: 0,
+            len: 0,
+        }
+    }
+}
+
+impl Iterator for AsciiValues<'_> {
+    type Item = u16;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position == self.len {
+            if self.input.is_empty() {
+                return None;
+            }
+
+            let (block, rest) = self.input.split_at(self.input.len().min(BLOCK_LEN));
+            self.input = rest;
+            self.position = 0;
+            self.len = ascii_to_values(block, &mut self.buffer, self.encoding);
+        }
+
+        let value = self.buffer[self.position];
+        self.position += 1;
+        Some(value.into())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = self.len - self.position;
+        (buffered, Some(buffered + self.input.len()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::convert::TryFrom;
+
+    const ENCODINGS: [Encoding; 2] = [Encoding::EncodedUriComponent, Encoding::Base64];
+
+    /// Bytes with runs of valid chars, to use both the vector and the scalar paths.
+    fn test_bytes() -> Vec<u8> {
+        let mut bytes: Vec<u8> = (0..=255).collect();
+        bytes.extend(BASE64_KEY.iter().chain(URI_KEY).cycle().take(1000));
+        for i in 0..=255 {
+            bytes.extend(URI_KEY.iter().take(40));
+            bytes.push(i);
+        }
+        bytes
+    }
+
+    #[test]
+    fn values_to_ascii_matches_scalar() {
+        for encoding in ENCODINGS {
+            for len in 0..200 {
+                let mut values: Vec<u8> = (0..len).map(|i| i % 64).collect();
+                let expected: Vec<u8> = values
+                    .iter()
+                    .map(|&value| key(encoding)[usize::from(value)])
+                    .collect();
+
+                values_to_ascii(&mut values, encoding);
+                assert_eq!(values, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn ascii_to_values_matches_scalar() {
+        let bytes = test_bytes();
+        for encoding in ENCODINGS {
+            for start in 0..40 {
+                let input = &bytes[start..];
+
+                let mut expected = vec![0; input.len()];
+                let len = ascii_to_values_scalar(input, &mut expected, encoding);
+                expected.truncate(len);
+
+                let mut output = vec![0; input.len()];
+                let len = ascii_to_values(input, &mut output, encoding);
+                output.truncate(len);
+                assert_eq!(output, expected);
+
+                let values: Vec<u8> = AsciiValues::new(input, encoding)
+                    .map(|value| u8::try_from(value).unwrap())
+                    .collect();
+                assert_eq!(values, expected);
+            }
+        }
+    }
+}
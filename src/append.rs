@@ -0,0 +1,181 @@
+use crate::compress::compress_with_encoding;
+use crate::compress::BitWriter;
+use crate::compress::CompressContext;
+use crate::compress::HashMap;
+use crate::compress::HashSet;
+use crate::constants::NUM_BASE_CODES;
+use crate::encoding::Encoding;
+use crate::token::Token;
+use crate::token::Tokenizer;
+use crate::IntoWideIter;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::ops::Range;
+
+/// The last code of a stream before its close code.
+#[derive(Debug)]
+struct LastCode {
+    /// The chars this code produced, in the decompressed data.
+    range: Range<usize>,
+
+    /// Whether this code was a new char.
+    is_char: bool,
+
+    /// The bit position of the code.
+    bit_position: usize,
+
+    /// The code bookkeeping before this code.
+    enlarge_in: u64,
+    num_bits: u8,
+}
+
+/// Append data to a compressed stream, without compressing it all again.
+///
+/// The compressor's state is rebuilt while decompressing the stream.
+/// Compression then continues from the start of the last code,
+/// as the compressor had not yet decided where the last word ends.
+/// For streams from this library or the reference implementation,
+/// the result is identical to compressing all of the data at once.
+/// Streams that cannot be continued are compressed again from the start.
+///
+/// The result contains the chars of the given [`Encoding`], including its padding.
+///
+/// # Errors
+/// Returns `None` if the compressed data could not be decompressed.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::Encoding;
+/// let compressed = lz_str::compress_to_base64("The quick brown fox");
+/// let appended = lz_str::append(&compressed, Encoding::Base64, " jumps over the lazy dog")
+///     .expect("invalid data");
+///
+/// let expected = lz_str::compress_to_base64("The quick brown fox jumps over the lazy dog");
+/// assert_eq!(String::from_utf16(&appended).unwrap(), expected);
+/// ```
+pub fn append(
+    compressed: impl IntoWideIter,
+    encoding: Encoding,
+    data: impl IntoWideIter,
+) -> Option<Vec<u16>> {
+    let values: Vec<u16> = encoding.values(compressed.into_wide_iter()).collect();
+
+    // Decompress, remembering where each dictionary entry is in the decompressed data.
+    // Entries are always a word followed by the first char of the next one,
+    // so they are all contiguous.
+    let mut input = Vec::new();
+    let mut entries: Vec<Range<usize>> = vec![0..0; usize::from(NUM_BASE_CODES)];
+    let mut last_code: Option<LastCode> = None;
+    let mut tokenizer = Tokenizer::new(values.iter().copied(), encoding.bits_per_char());
+    loop {
+        let bit_position = tokenizer.bit_position();
+        let enlarge_in = tokenizer.state().enlarge_in();
+        let num_bits = tokenizer.state().num_bits();
+
+        let token = match tokenizer.next() {
+            Some(token) => token.ok()?,
+            // An empty stream.
+            None => break,
+        };
+
+        let start = input.len();
+        let is_char = match token {
+            Token::U8(c) => {
+                input.push(u16::from(c));
+                entries.push(start..start + 1);
+                true
+            }
+            Token::U16(c) => {
+                input.push(c);
+                entries.push(start..start + 1);
+                true
+            }
+            Token::Reference(code) => {
+                match entries.get(usize::try_from(code).ok()?) {
+                    Some(entry) => input.extend_from_within(entry.clone()),
+                    // The entry that is about to be created is w+w[0].
+                    None => {
+                        let w = last_code.as_ref()?.range.clone();
+                        input.extend_from_within(w.clone());
+                        input.push(input[w.start]);
+                    }
+                }
+                false
+            }
+            Token::Close => break,
+        };
+
+        // Add w+entry[0] to the dictionary.
+        if let Some(last_code) = last_code.as_ref() {
+            entries.push(last_code.range.start..start + 1);
+        }
+
+        last_code = Some(LastCode {
+            range: start..input.len(),
+            is_char,
+            bit_position,
+            enlarge_in,
+            num_bits,
+        });
+    }
+
+    let last_code = match last_code {
+        Some(last_code) => last_code,
+        None => {
+            let data: Vec<u16> = data.into_wide_iter().collect();
+            return Some(compress_with_encoding(&data, encoding));
+        }
+    };
+
+    let compressed_len = input.len();
+    input.extend(data.into_wide_iter());
+
+    let num_entries = entries.len() - usize::from(NUM_BASE_CODES);
+    let mut dictionary = HashMap::default();
+    for (code, entry) in entries
+        .into_iter()
+        .enumerate()
+        .skip(usize::from(NUM_BASE_CODES))
+    {
+        dictionary
+            .entry(&input[entry])
+            .or_insert(u32::try_from(code).ok()?);
+    }
+
+    // The compressor never creates duplicate entries and numbers new entries by the dictionary size.
+    // Streams with duplicates, like the ones from `CompressionLevel::Best`, cannot be continued.
+    if dictionary.len() != num_entries {
+        return Some(compress_with_encoding(&input, encoding));
+    }
+
+    // The last word is the only one that may not have been produced yet.
+    let mut dictionary_to_create: HashSet<_> = HashSet::default();
+    if last_code.is_char {
+        dictionary_to_create.insert(input[last_code.range.start]);
+    }
+
+    let writer = BitWriter::resume(
+        &values,
+        last_code.bit_position,
+        encoding.bits_per_char(),
+        |n| encoding.value_to_char(n),
+    );
+    let mut ctx = CompressContext::from_parts(
+        &input,
+        dictionary,
+        dictionary_to_create,
+        last_code.range,
+        last_code.enlarge_in,
+        last_code.num_bits,
+        writer,
+    );
+    for i in compressed_len..input.len() {
+        ctx.write_u16(i);
+    }
+
+    let mut compressed = ctx.finish();
+    encoding.pad(&mut compressed);
+
+    Some(compressed)
+}
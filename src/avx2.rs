@@ -0,0 +1,145 @@
+//! The AVX2 versions of the bulk mappings in [`crate::alphabet`].
+//!
+//! This is the only module that may use `unsafe`.
+
+use crate::alphabet::ascii_to_values_scalar;
+use crate::encoding::Encoding;
+use core::arch::x86_64::*;
+
+const LANES: usize = 32;
+
+/// Map the values of all full blocks, returning the remaining values.
+pub(crate) fn values_to_ascii<'a>(values: &'a mut [u8], key: &[u8]) -> &'a mut [u8] {
+    let mut blocks = values.chunks_exact_mut(LANES);
+    for block in &mut blocks {
+        // SAFETY: The caller checked that the CPU supports AVX2.
+        unsafe { values_to_ascii_block(block, key) };
+    }
+    blocks.into_remainder()
+}
+
+/// Map the bytes of all blocks, falling back to the scalar version for blocks with invalid bytes.
+pub(crate) fn ascii_to_values(input: &[u8], output: &mut [u8], encoding: Encoding) -> usize {
+    let mut len = 0;
+    let mut blocks = input.chunks_exact(LANES);
+    for block in &mut blocks {
+        let output = &mut output[len..len + LANES];
+        // SAFETY: The caller checked that the CPU supports AVX2.
+        if unsafe { ascii_to_values_block(block, output, encoding) } {
+            len += LANES;
+        } else {
+            len += ascii_to_values_scalar(block, output, encoding);
+        }
+    }
+    len + ascii_to_values_scalar(blocks.remainder(), &mut output[len..], encoding)
+}
+
+/// # Safety
+/// The CPU must support AVX2.
+#[target_feature(enable = "avx2")]
+unsafe fn values_to_ascii_block(block: &mut [u8], key: &[u8]) {
+    assert_eq!(block.len(), LANES);
+
+    let values = _mm256_loadu_si256(block.as_ptr().cast());
+    let clamped = _mm256_min_epu8(values, _mm256_set1_epi8(63));
+    assert_eq!(
+        _mm256_movemask_epi8(_mm256_cmpeq_epi8(clamped, values)),
+        -1,
+        "values must be less than 64"
+    );
+
+    // Pick the offset from the value to its char,
+    // using 13 for 0..26, 0 for 26..52, and 1..13 for 52..64.
+    let index = _mm256_subs_epu8(values, _mm256_set1_epi8(51));
+    let is_upper = _mm256_cmpgt_epi8(_mm256_set1_epi8(26), values);
+    let index = _mm256_or_si256(index, _mm256_and_si256(is_upper, _mm256_set1_epi8(13)));
+
+    let digit = (b'0' as i8).wrapping_sub(52);
+    let c62 = (key[62] as i8).wrapping_sub(62);
+    let c63 = (key[63] as i8).wrapping_sub(63);
+    #[rustfmt::skip]
+    let offsets = _mm256_setr_epi8(
+        b'a' as i8 - 26, digit, digit, digit, digit, digit, digit, digit, digit, digit, digit,
+        c62, c63, b'A' as i8, 0, 0,
+        b'a' as i8 - 26, digit, digit, digit, digit, digit, digit, digit, digit, digit, digit,
+        c62, c63, b'A' as i8, 0, 0,
+    );
+    let chars = _mm256_add_epi8(values, _mm256_shuffle_epi8(offsets, index));
+
+    _mm256_storeu_si256(block.as_mut_ptr().cast(), chars);
+}
+
+/// Map a block where every byte is part of the alphabet.
+///
+/// Returns `false` without writing anything if a byte is not.
+///
+/// # Safety
+/// The CPU must support AVX2.
+#[target_feature(enable = "avx2")]
+unsafe fn ascii_to_values_block(block: &[u8], output: &mut [u8], encoding: Encoding) -> bool {
+    assert_eq!(block.len(), LANES);
+    assert_eq!(output.len(), LANES);
+
+    // The chars of the values other than letters and digits.
+    let (c62, other_c62, c63, c64) = match encoding {
+        // Spaces may have been decoded from a '+'.
+        Encoding::EncodedUriComponent => (b'+', b' ', b'-', b'$'),
+        Encoding::Base64 => (b'+', b'+', b'/', b'='),
+        Encoding::Raw | Encoding::Utf16 => unreachable!(),
+    };
+
+    let bytes = _mm256_loadu_si256(block.as_ptr().cast());
+    let is_upper = in_range(bytes, b'A', b'Z');
+    let is_lower = in_range(bytes, b'a', b'z');
+    let is_digit = in_range(bytes, b'0', b'9');
+    let is_62 = _mm256_or_si256(
+        _mm256_cmpeq_epi8(bytes, _mm256_set1_epi8(c62 as i8)),
+        _mm256_cmpeq_epi8(bytes, _mm256_set1_epi8(other_c62 as i8)),
+    );
+    let is_63 = _mm256_cmpeq_epi8(bytes, _mm256_set1_epi8(c63 as i8));
+    let is_64 = _mm256_cmpeq_epi8(bytes, _mm256_set1_epi8(c64 as i8));
+
+    let is_alphanumeric = _mm256_or_si256(_mm256_or_si256(is_upper, is_lower), is_digit);
+    let is_special = _mm256_or_si256(_mm256_or_si256(is_62, is_63), is_64);
+    let is_valid = _mm256_or_si256(is_alphanumeric, is_special);
+    if _mm256_movemask_epi8(is_valid) != -1 {
+        return false;
+    }
+
+    let offsets = _mm256_or_si256(
+        _mm256_or_si256(
+            _mm256_and_si256(is_upper, _mm256_set1_epi8(-(b'A' as i8))),
+            _mm256_and_si256(is_lower, _mm256_set1_epi8(26 - b'a' as i8)),
+        ),
+        _mm256_and_si256(is_digit, _mm256_set1_epi8(52 - b'0' as i8)),
+    );
+    let alphanumeric = _mm256_and_si256(_mm256_add_epi8(bytes, offsets), is_alphanumeric);
+    let special = _mm256_or_si256(
+        _mm256_or_si256(
+            _mm256_and_si256(is_62, _mm256_set1_epi8(62)),
+            _mm256_and_si256(is_63, _mm256_set1_epi8(63)),
+        ),
+        _mm256_and_si256(is_64, _mm256_set1_epi8(64)),
+    );
+
+    _mm256_storeu_si256(
+        output.as_mut_ptr().cast(),
+        _mm256_or_si256(alphanumeric, special),
+    );
+    true
+}
+
+/// Get a mask of the bytes in `first..=last`.
+///
+/// Bytes over 127 are negative, so they are never in an ASCII range.
+///
+/// # Safety
+/// The CPU must support AVX2.
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn in_range(bytes: __m256i, first: u8, last: u8) -> __m256i {
+    _mm256_and_si256(
+        _mm256_cmpgt_epi8(bytes, _mm256_set1_epi8(first as i8 - 1)),
+        _mm256_cmpgt_epi8(_mm256_set1_epi8(last as i8 + 1), bytes),
+    )
+}
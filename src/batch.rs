@@ -0,0 +1,112 @@
+use crate::dictionary::LinkedDictionary;
+use crate::encoding::Encoding;
+use crate::stream::Compressor;
+use crate::token::Token;
+use crate::token::TokenError;
+use crate::token::Tokenizer;
+use crate::IntoWideIter;
+use rayon::iter::IntoParallelRefIterator;
+use rayon::iter::ParallelIterator;
+
+/// Compress many independent inputs in parallel.
+///
+/// Each output is identical to the output of the compress function for the encoding, as `u16`s.
+/// For encodings other than [`Encoding::Raw`], they are valid UTF-16.
+/// Each thread reuses its dictionary between inputs,
+/// so this is much faster than compressing many small inputs one at a time.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::Encoding;
+/// let inputs = ["The quick brown fox", "jumps over", "the lazy dog"];
+/// let compressed = lz_str::compress_batch(&inputs, Encoding::Base64);
+///
+/// for (input, compressed) in inputs.iter().zip(compressed) {
+///     assert_eq!(String::from_utf16(&compressed).unwrap(), lz_str::compress_to_base64(*input));
+/// }
+/// ```
+pub fn compress_batch<T>(inputs: &[T], encoding: Encoding) -> Vec<Vec<u16>>
+where
+    T: Sync,
+    for<'a> &'a T: IntoWideIter,
+{
+    inputs
+        .par_iter()
+        .map_init(
+            || Compressor::new(encoding),
+            |compressor, input| {
+                let mut output = Vec::new();
+                compressor.write(input, &mut output);
+                compressor.finish_and_reset(&mut output);
+                output
+            },
+        )
+        .collect()
+}
+
+/// Decompress many independent inputs in parallel.
+///
+/// Each result is the decompressed data of the input at the same index,
+/// or the error that stopped it from being decompressed.
+/// Each thread reuses its dictionary between inputs,
+/// so this is much faster than decompressing many small inputs one at a time.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::{Encoding, TokenError};
+/// let inputs = [
+///     lz_str::compress_to_base64("The quick brown fox"),
+///     String::from("invalid"),
+/// ];
+/// let decompressed = lz_str::decompress_batch(&inputs, Encoding::Base64);
+///
+/// let expected: Vec<u16> = "The quick brown fox".encode_utf16().collect();
+/// assert_eq!(decompressed[0], Ok(expected));
+/// assert!(decompressed[1].is_err());
+/// ```
+pub fn decompress_batch<T>(inputs: &[T], encoding: Encoding) -> Vec<Result<Vec<u16>, TokenError>>
+where
+    T: Sync,
+    for<'a> &'a T: IntoWideIter,
+{
+    inputs
+        .par_iter()
+        .map_init(LinkedDictionary::new, |dictionary, input| {
+            dictionary.clear();
+            decompress_with_dictionary(input, encoding, dictionary)
+        })
+        .collect()
+}
+
+/// Decompress data with a dictionary that only holds the base codes.
+fn decompress_with_dictionary(
+    compressed: impl IntoWideIter,
+    encoding: Encoding,
+    dictionary: &mut LinkedDictionary,
+) -> Result<Vec<u16>, TokenError> {
+    let compressed = encoding.values(compressed.into_wide_iter());
+
+    let mut output = Vec::new();
+    for token in Tokenizer::new(compressed, encoding.bits_per_char()) {
+        let token = token?;
+        if token == Token::Close {
+            break;
+        }
+
+        match dictionary.push(token) {
+            Some(code) => {
+                // The code was just pushed.
+                let len = dictionary.get(code).unwrap().len;
+                dictionary.extend_from_entry(code, 0, len, &mut output);
+            }
+            // Only a reference to an entry that does not exist can fail.
+            None => match token {
+                Token::Reference(code) => return Err(TokenError::InvalidReference(code)),
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    // Only an empty input has no close code.
+    Ok(output)
+}
@@ -0,0 +1,314 @@
+use crate::compress::compress_with_encoding;
+use crate::encoding::Encoding;
+use crate::frame::decompress_payload;
+use crate::frame::encoding_from_id;
+use crate::frame::encoding_id;
+use crate::frame::write_payload;
+use crate::frame::FrameError;
+use crate::IntoWideIter;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::convert::TryInto;
+use core::ops::Range;
+
+/// The first bytes of a chunked container.
+const MAGIC: [u8; 4] = *b"LZSC";
+
+/// The version of the container format this library writes.
+const VERSION: u8 = 1;
+
+/// The number of bytes in a container header, before the index.
+const HEADER_LEN: usize = 4 + 1 + 1 + 4;
+
+/// The number of bytes in an index entry.
+const ENTRY_LEN: usize = 8 + 8;
+
+/// Run a function over each item, spreading the items over all available threads.
+#[cfg(feature = "std")]
+fn par_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let num_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    if num_threads == 1 || items.len() <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let group_len = items.len().div_ceil(num_threads);
+    let f = &f;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(group_len)
+            .map(|group| scope.spawn(move || group.iter().map(f).collect::<Vec<R>>()))
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+/// Run a function over each item.
+///
+/// Without `std` there are no threads, so this runs on the current thread.
+#[cfg(not(feature = "std"))]
+fn par_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    F: Fn(&T) -> R,
+{
+    items.iter().map(f).collect()
+}
+
+/// Compress data into a chunked container.
+///
+/// The data is split into chunks of `chunk_len` `u16`s,
+/// which are compressed in parallel as independent streams.
+/// Without the `std` feature, the chunks are compressed one after another.
+/// An index of the compressed and decompressed length of each chunk allows [`ChunkedReader`]
+/// to decompress chunks in parallel or only the chunks covering a range.
+///
+/// A container is laid out as:
+///
+/// | Bytes  | Field |
+/// |--------|-------|
+/// | 4      | The magic bytes, "LZSC" |
+/// | 1      | The version, currently 1 |
+/// | 1      | The [`Encoding`] of the chunks |
+/// | 4      | The number of chunks, little endian |
+/// | 16 * n | The length of each chunk's payload in bytes and its decompressed length in `u16`s, as little endian `u64`s |
+/// | ...    | The payload of each chunk |
+///
+/// Each payload is stored like the payload of [`crate::encode_framed`],
+/// so it can be decompressed by any lz-string implementation.
+///
+/// # Panics
+/// Panics if `chunk_len` is 0,
+/// or if the data would be split into more than `u32::MAX` chunks,
+/// which can only happen with over 4 billion `u16`s of data.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::{ChunkedReader, Encoding};
+/// let data = "The quick brown fox jumps over the lazy dog. ".repeat(100);
+/// let container = lz_str::compress_chunked(&data, Encoding::Base64, 1000);
+///
+/// let reader = ChunkedReader::new(&container).expect("invalid container");
+/// assert_eq!(reader.num_chunks(), 5);
+///
+/// let fox: Vec<u16> = "fox".encode_utf16().collect();
+/// assert_eq!(reader.extract(2266..2269), Ok(fox));
+/// ```
+pub fn compress_chunked(data: impl IntoWideIter, encoding: Encoding, chunk_len: usize) -> Vec<u8> {
+    assert!(chunk_len > 0);
+
+    let data: Vec<u16> = data.into_wide_iter().collect();
+    let chunks: Vec<&[u16]> = data.chunks(chunk_len).collect();
+    let num_chunks = u32::try_from(chunks.len()).expect("too many chunks");
+    let payloads = par_map(&chunks, |chunk| {
+        let mut payload = Vec::new();
+        write_payload(
+            &compress_with_encoding(chunk, encoding),
+            encoding,
+            &mut payload,
+        );
+        payload
+    });
+
+    let payloads_len: usize = payloads.iter().map(Vec::len).sum();
+    let mut container = Vec::with_capacity(HEADER_LEN + chunks.len() * ENTRY_LEN + payloads_len);
+    container.extend(MAGIC);
+    container.push(VERSION);
+    container.push(encoding_id(encoding));
+    container.extend(num_chunks.to_le_bytes());
+    for (chunk, payload) in chunks.iter().zip(payloads.iter()) {
+        // usize is at most 64 bits.
+        container.extend(u64::try_from(payload.len()).unwrap().to_le_bytes());
+        container.extend(u64::try_from(chunk.len()).unwrap().to_le_bytes());
+    }
+    for payload in payloads {
+        container.extend(payload);
+    }
+
+    container
+}
+
+/// A chunk in the index of a container.
+#[derive(Debug, Clone)]
+struct Chunk {
+    /// The bytes of the payload, in the container.
+    payload: Range<usize>,
+
+    /// The decompressed data, in the decompressed data of the container.
+    decompressed: Range<usize>,
+}
+
+/// A reader for a container from [`compress_chunked`].
+///
+/// Creating a reader only reads the index.
+/// Chunks are decompressed on demand, in parallel.
+#[derive(Debug, Clone)]
+pub struct ChunkedReader<'a> {
+    container: &'a [u8],
+    encoding: Encoding,
+    chunks: Vec<Chunk>,
+
+    /// The length of the decompressed data.
+    len: usize,
+}
+
+impl<'a> ChunkedReader<'a> {
+    /// Read the index of a container.
+    ///
+    /// # Errors
+    /// Returns an error if the header or index is invalid.
+    pub fn new(container: &'a [u8]) -> Result<Self, FrameError> {
+        if container.len() < HEADER_LEN {
+            return Err(FrameError::TooShort);
+        }
+        let (header, rest) = container.split_at(HEADER_LEN);
+
+        if header[..4] != MAGIC {
+            return Err(FrameError::InvalidMagic);
+        }
+
+        let version = header[4];
+        if version != VERSION {
+            return Err(FrameError::UnsupportedVersion(version));
+        }
+
+        let encoding = encoding_from_id(header[5])?;
+
+        // The slices have the right lengths.
+        let num_chunks = u32::from_le_bytes(header[6..10].try_into().unwrap());
+        let index_len = usize::try_from(num_chunks)
+            .ok()
+            .and_then(|n| n.checked_mul(ENTRY_LEN))
+            .ok_or(FrameError::TooShort)?;
+        if rest.len() < index_len {
+            return Err(FrameError::TooShort);
+        }
+        let (index, payloads) = rest.split_at(index_len);
+
+        let mut chunks = Vec::with_capacity(index.len() / ENTRY_LEN);
+        let mut payload_start = HEADER_LEN + index_len;
+        let mut len: usize = 0;
+        for entry in index.chunks(ENTRY_LEN) {
+            let payload_len = u64::from_le_bytes(entry[..8].try_into().unwrap());
+            let decompressed_len = u64::from_le_bytes(entry[8..].try_into().unwrap());
+
+            let payload_end = usize::try_from(payload_len)
+                .ok()
+                .and_then(|n| payload_start.checked_add(n))
+                .ok_or(FrameError::TooShort)?;
+            let decompressed_end = usize::try_from(decompressed_len)
+                .ok()
+                .and_then(|n| len.checked_add(n))
+                .ok_or(FrameError::InvalidPayload)?;
+
+            chunks.push(Chunk {
+                payload: payload_start..payload_end,
+                decompressed: len..decompressed_end,
+            });
+            payload_start = payload_end;
+            len = decompressed_end;
+        }
+        if payload_start - HEADER_LEN - index_len > payloads.len() {
+            return Err(FrameError::TooShort);
+        }
+
+        Ok(ChunkedReader {
+            container,
+            encoding,
+            chunks,
+            len,
+        })
+    }
+
+    /// The encoding of the chunks.
+    #[inline]
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// The number of chunks.
+    #[inline]
+    pub fn num_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// The length of the decompressed data.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the decompressed data is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Decompress all of the data.
+    ///
+    /// # Errors
+    /// Returns an error if a chunk could not be decompressed,
+    /// or if its decompressed length does not match the index.
+    pub fn decompress(&self) -> Result<Vec<u16>, FrameError> {
+        self.extract(0..self.len)
+    }
+
+    /// Decompress part of the data.
+    ///
+    /// Only the chunks covering the range are decompressed.
+    ///
+    /// # Errors
+    /// Returns an error if the range is out of bounds,
+    /// if a chunk could not be decompressed,
+    /// or if its decompressed length does not match the index.
+    pub fn extract(&self, range: Range<usize>) -> Result<Vec<u16>, FrameError> {
+        if range.start > range.end || range.end > self.len {
+            return Err(FrameError::OutOfBounds);
+        }
+
+        let first = self
+            .chunks
+            .partition_point(|chunk| chunk.decompressed.end <= range.start);
+        let last = self
+            .chunks
+            .partition_point(|chunk| chunk.decompressed.start < range.end);
+        let chunks = &self.chunks[first..last.max(first)];
+
+        let decompressed = par_map(chunks, |chunk| {
+            let decompressed =
+                decompress_payload(&self.container[chunk.payload.clone()], self.encoding)?;
+
+            if decompressed.len() != chunk.decompressed.len() {
+                // usize is at most 64 bits.
+                return Err(FrameError::LengthMismatch {
+                    expected: u64::try_from(chunk.decompressed.len()).unwrap(),
+                    actual: u64::try_from(decompressed.len()).unwrap(),
+                });
+            }
+
+            Ok(decompressed)
+        });
+
+        // The lengths in the index are untrusted until every chunk matches them,
+        // so only allocate for the output after that.
+        let decompressed = decompressed.into_iter().collect::<Result<Vec<_>, _>>()?;
+        let parts: Vec<&[u16]> = chunks
+            .iter()
+            .zip(&decompressed)
+            .map(|(chunk, decompressed)| {
+                let start = range.start.saturating_sub(chunk.decompressed.start);
+                let end = range.end.min(chunk.decompressed.end) - chunk.decompressed.start;
+                &decompressed[start..end]
+            })
+            .collect();
+
+        Ok(parts.concat())
+    }
+}
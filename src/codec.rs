@@ -0,0 +1,323 @@
+use crate::compress::compress_with_encoding;
+use crate::encoding::Encoding;
+use crate::frame::decompress_payload;
+use crate::frame::write_payload;
+use crate::frame::FrameError;
+use crate::token::TokenError;
+use crate::IntoWideIter;
+use bytes::Buf;
+use bytes::BufMut;
+use bytes::BytesMut;
+use std::convert::TryFrom;
+use std::convert::TryInto;
+use std::io;
+use tokio_util::codec::Decoder;
+use tokio_util::codec::Encoder;
+
+/// The default for [`LzStringCodec::with_max_len`], 8 MiB.
+const DEFAULT_MAX_LEN: usize = 8 * 1024 * 1024;
+
+/// The number of bytes in the prefix of [`Framing::LengthPrefix`].
+const PREFIX_LEN: usize = 4;
+
+/// How messages are separated in a byte stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Framing {
+    /// Each message is followed by a delimiter byte, like `b'\n'` for a line protocol.
+    ///
+    /// The delimiter must be an ASCII byte that the encoding never produces.
+    /// For [`Encoding::Utf16`], that is any control char, like `b'\n'` or `b'\0'`.
+    /// This does not work with [`Encoding::Raw`], which may produce any byte.
+    Delimiter(u8),
+
+    /// Each message is preceded by its length in bytes, as a big endian `u32`.
+    LengthPrefix,
+}
+
+/// An error from [`LzStringCodec`].
+#[derive(Debug)]
+pub enum CodecError {
+    /// The message is longer than the limit set with [`LzStringCodec::with_max_len`].
+    TooLong {
+        /// The length of the message, or the number of bytes received without finding its end.
+        len: usize,
+
+        /// The limit.
+        max_len: usize,
+    },
+
+    /// The message is not valid for its encoding, like invalid UTF-8.
+    InvalidPayload,
+
+    /// The message could not be decompressed.
+    InvalidData(TokenError),
+
+    /// The decompressed message is not valid UTF-16.
+    InvalidUtf16,
+
+    /// An error from the underlying stream.
+    Io(io::Error),
+}
+
+impl std::fmt::Display for CodecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooLong { len, max_len } => write!(
+                f,
+                "the message is {len} bytes long, but the limit is {max_len} bytes"
+            ),
+            Self::InvalidPayload => write!(f, "the message is not valid for its encoding"),
+            Self::InvalidData(error) => write!(f, "invalid compressed data: {error}"),
+            Self::InvalidUtf16 => write!(f, "the decompressed message is not valid UTF-16"),
+            Self::Io(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidData(error) => Some(error),
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CodecError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<FrameError> for CodecError {
+    fn from(error: FrameError) -> Self {
+        match error {
+            FrameError::InvalidData(error) => Self::InvalidData(error),
+            _ => Self::InvalidPayload,
+        }
+    }
+}
+
+/// A codec for messages compressed with lz-string, for use with [`tokio_util::codec::Framed`].
+///
+/// Each message is the output of the compress function for the encoding,
+/// stored as UTF-8 like the strings sent by the JS version over websockets and text protocols.
+/// [`Encoding::Raw`] messages are stored like the output of [`crate::compress_to_uint8_array`].
+///
+/// Messages are decoded as decompressed [`String`]s,
+/// and anything that implements [`IntoWideIter`] can be encoded.
+///
+/// # Example
+/// ```rust
+/// # use bytes::BytesMut;
+/// # use lz_str::{Encoding, Framing, LzStringCodec};
+/// # use tokio_util::codec::{Decoder, Encoder};
+/// let mut codec = LzStringCodec::new(Encoding::Base64, Framing::Delimiter(b'\n'));
+///
+/// let mut buffer = BytesMut::new();
+/// codec.encode("The quick brown fox", &mut buffer).unwrap();
+/// assert_eq!(
+///     &buffer[..],
+///     format!("{}\n", lz_str::compress_to_base64("The quick brown fox")).as_bytes()
+/// );
+///
+/// let message = codec.decode(&mut buffer).unwrap();
+/// assert_eq!(message.as_deref(), Some("The quick brown fox"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LzStringCodec {
+    encoding: Encoding,
+    framing: Framing,
+    max_len: usize,
+
+    /// The number of bytes that were already searched for the delimiter.
+    searched: usize,
+}
+
+impl LzStringCodec {
+    /// Make a new [`LzStringCodec`].
+    ///
+    /// Messages are limited to 8 MiB, which can be changed with [`LzStringCodec::with_max_len`].
+    ///
+    /// # Panics
+    /// Panics if the framing uses a delimiter that the encoding may produce.
+    pub fn new(encoding: Encoding, framing: Framing) -> Self {
+        if let Framing::Delimiter(delimiter) = framing {
+            assert!(
+                is_valid_delimiter(encoding, delimiter),
+                "the delimiter {:#04x} may be produced by {:?}",
+                delimiter,
+                encoding
+            );
+        }
+
+        LzStringCodec {
+            encoding,
+            framing,
+            max_len: DEFAULT_MAX_LEN,
+
+            searched: 0,
+        }
+    }
+
+    /// Set the most bytes a message may take, not including its framing.
+    ///
+    /// Longer messages are errors, both when encoding and decoding.
+    #[must_use]
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Get the encoding of messages.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Get the framing of messages.
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Get the most bytes a message may take.
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Check the length of a message.
+    fn check_len(&self, len: usize) -> Result<(), CodecError> {
+        if len > self.max_len {
+            return Err(CodecError::TooLong {
+                len,
+                max_len: self.max_len,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Decompress a message.
+    fn decompress(&self, payload: &[u8]) -> Result<String, CodecError> {
+        let decompressed = decompress_payload(payload, self.encoding)?;
+        String::from_utf16(&decompressed).map_err(|_| CodecError::InvalidUtf16)
+    }
+}
+
+/// Whether the encoding never produces the delimiter.
+fn is_valid_delimiter(encoding: Encoding, delimiter: u8) -> bool {
+    match encoding {
+        Encoding::Raw => false,
+        // Any char of at least 32 may be produced.
+        Encoding::Utf16 => delimiter < 32,
+        Encoding::EncodedUriComponent | Encoding::Base64 => {
+            delimiter.is_ascii() && encoding.char_to_value(delimiter.into()).is_none()
+        }
+    }
+}
+
+impl Decoder for LzStringCodec {
+    type Item = String;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.framing {
+            Framing::Delimiter(delimiter) => {
+                let position = src[self.searched..]
+                    .iter()
+                    .position(|&b| b == delimiter)
+                    .map(|position| self.searched + position);
+
+                match position {
+                    Some(position) => {
+                        self.searched = 0;
+                        self.check_len(position)?;
+
+                        let payload = src.split_to(position);
+                        src.advance(1);
+                        self.decompress(&payload).map(Some)
+                    }
+                    None => {
+                        self.searched = src.len();
+                        self.check_len(src.len())?;
+                        Ok(None)
+                    }
+                }
+            }
+            Framing::LengthPrefix => {
+                let prefix = match src.get(..PREFIX_LEN) {
+                    // The slice always has 4 bytes.
+                    Some(prefix) => u32::from_be_bytes(prefix.try_into().unwrap()),
+                    None => return Ok(None),
+                };
+                let len = usize::try_from(prefix).unwrap_or(usize::MAX);
+                self.check_len(len)?;
+
+                if src.len() - PREFIX_LEN < len {
+                    src.reserve(PREFIX_LEN + len - src.len());
+                    return Ok(None);
+                }
+
+                src.advance(PREFIX_LEN);
+                let payload = src.split_to(len);
+                self.decompress(&payload).map(Some)
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if let Some(message) = self.decode(src)? {
+            return Ok(Some(message));
+        }
+
+        match self.framing {
+            // The last message may not have a delimiter.
+            Framing::Delimiter(_) if !src.is_empty() => {
+                self.searched = 0;
+                let payload = src.split();
+                self.decompress(&payload).map(Some)
+            }
+            _ if !src.is_empty() => Err(CodecError::Io(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the stream ended in the middle of a message",
+            ))),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl<T> Encoder<T> for LzStringCodec
+where
+    T: IntoWideIter,
+{
+    type Error = CodecError;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let data: Vec<u16> = item.into_wide_iter().collect();
+        let compressed = compress_with_encoding(&data, self.encoding);
+
+        let mut payload = Vec::new();
+        write_payload(&compressed, self.encoding, &mut payload);
+        self.check_len(payload.len())?;
+
+        match self.framing {
+            Framing::Delimiter(delimiter) => {
+                dst.reserve(payload.len() + 1);
+                dst.put_slice(&payload);
+                dst.put_u8(delimiter);
+            }
+            Framing::LengthPrefix => {
+                let len = u32::try_from(payload.len()).map_err(|_| CodecError::TooLong {
+                    len: payload.len(),
+                    max_len: self.max_len,
+                })?;
+
+                dst.reserve(PREFIX_LEN + payload.len());
+                dst.put_u32(len);
+                dst.put_slice(&payload);
+            }
+        }
+
+        Ok(())
+    }
+}
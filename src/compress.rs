@@ -1,35 +1,57 @@
-use crate::constants::BASE64_KEY;
+use crate::alphabet::has_simd;
+use crate::alphabet::values_to_ascii;
 use crate::constants::CLOSE_CODE;
+use crate::constants::NUM_BASE_CODES;
 use crate::constants::START_CODE_BITS;
 use crate::constants::U16_CODE;
 use crate::constants::U8_CODE;
-use crate::constants::URI_KEY;
+use crate::encoding::Encoding;
+use crate::flexible::flexible_parse;
+use crate::small::compress_small;
+use crate::small::InlineVec;
+use crate::small::SMALL_LEN;
+use crate::token::encode_tokens;
 use crate::IntoWideIter;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::convert::TryInto;
+use core::hash::BuildHasher;
+
+#[cfg(feature = "std")]
 use std::collections::hash_map::Entry as HashMapEntry;
-use std::convert::TryInto;
 
-#[cfg(not(feature = "rustc-hash"))]
-type HashMap<K, V> = std::collections::HashMap<K, V>;
+#[cfg(not(feature = "std"))]
+use hashbrown::hash_map::Entry as HashMapEntry;
 
-#[cfg(not(feature = "rustc-hash"))]
-type HashSet<T> = std::collections::HashSet<T>;
+/// The hasher used by maps unless another one is chosen.
+#[cfg(all(feature = "std", not(feature = "rustc-hash")))]
+pub(crate) type DefaultHashBuilder = std::collections::hash_map::RandomState;
 
+/// The hasher used by maps unless another one is chosen.
 #[cfg(feature = "rustc-hash")]
-type HashMap<K, V> = rustc_hash::FxHashMap<K, V>;
+pub(crate) type DefaultHashBuilder = core::hash::BuildHasherDefault<rustc_hash::FxHasher>;
 
-#[cfg(feature = "rustc-hash")]
-type HashSet<T> = rustc_hash::FxHashSet<T>;
+/// The hasher used by maps unless another one is chosen.
+#[cfg(all(not(feature = "std"), not(feature = "rustc-hash")))]
+pub(crate) type DefaultHashBuilder = hashbrown::DefaultHashBuilder;
 
-/// The number of "base codes",
-/// the default codes of all streams.
-///
-/// These are U8_CODE, U16_CODE, and CLOSE_CODE.
-const NUM_BASE_CODES: usize = 3;
+#[cfg(feature = "std")]
+pub(crate) type HashMap<K, V, S = DefaultHashBuilder> = std::collections::HashMap<K, V, S>;
+
+#[cfg(feature = "std")]
+pub(crate) type HashSet<T, S = DefaultHashBuilder> = std::collections::HashSet<T, S>;
+
+#[cfg(not(feature = "std"))]
+pub(crate) type HashMap<K, V, S = DefaultHashBuilder> = hashbrown::HashMap<K, V, S>;
+
+#[cfg(not(feature = "std"))]
+pub(crate) type HashSet<T, S = DefaultHashBuilder> = hashbrown::HashSet<T, S>;
 
 #[derive(Debug)]
-pub(crate) struct CompressContext<'a, F> {
-    dictionary: HashMap<&'a [u16], u32>,
-    dictionary_to_create: HashSet<u16>,
+pub(crate) struct CompressContext<'a, F, O = Vec<u16>, S = DefaultHashBuilder> {
+    dictionary: HashMap<&'a [u16], u32, S>,
+    dictionary_to_create: HashSet<u16, S>,
 
     /// The current word, w,
     /// in terms of indexes into the input.
@@ -43,12 +65,6 @@ pub(crate) struct CompressContext<'a, F> {
     /// The input buffer.
     input: &'a [u16],
 
-    /// The output buffer.
-    output: Vec<u16>,
-
-    /// The bit buffer.
-    bit_buffer: u16,
-
     /// The current number of bits in a code.
     ///
     /// This is a u8,
@@ -56,18 +72,8 @@ pub(crate) struct CompressContext<'a, F> {
     /// 32 < u8::MAX
     num_bits: u8,
 
-    /// The current bit position.
-    bit_position: u8,
-
-    /// The maximum # of bits per char.
-    ///
-    /// This value may not exceed 16,
-    /// as the reference implementation will also not handle values over 16.
-    bits_per_char: u8,
-
-    /// A transformation function to map a u16 to another u16,
-    /// before appending it to the output buffer.
-    to_char: F,
+    /// The packer for the output codes.
+    writer: BitWriter<F, O>,
 }
 
 impl<'a, F> CompressContext<'a, F>
@@ -80,11 +86,84 @@ where
     /// Panics if `bits_per_char` exceeds the number of bits in a u16.
     #[inline]
     pub fn new(input: &'a [u16], bits_per_char: u8, to_char: F) -> Self {
-        assert!(usize::from(bits_per_char) <= std::mem::size_of::<u16>() * 8);
+        // Lowball, assume we can get a 50% reduction in size.
+        let output = Vec::with_capacity(input.len() >> 1);
+        Self::with_output(input, output, bits_per_char, to_char)
+    }
+
+    /// Get the number of bits the stream would have if it was finished now,
+    /// before the last char is padded.
+    pub fn finished_bits(&self) -> usize {
+        let mut bits = self.writer.bits_written();
+        let mut num_bits = self.num_bits;
+        let mut enlarge_in = self.enlarge_in;
+        let mut decrement_enlarge_in = |num_bits: &mut u8| {
+            enlarge_in -= 1;
+            if enlarge_in == 0 {
+                enlarge_in = 1 << *num_bits;
+                *num_bits += 1;
+            }
+        };
+
+        // This follows `produce_w`.
+        let w = &self.input[self.w_start_idx..self.w_end_idx];
+        if let Some(first_w_char) = w.first() {
+            if self.dictionary_to_create.contains(first_w_char) {
+                bits += usize::from(num_bits) + if *first_w_char < 256 { 8 } else { 16 };
+                decrement_enlarge_in(&mut num_bits);
+            } else {
+                bits += usize::from(num_bits);
+            }
+            decrement_enlarge_in(&mut num_bits);
+        }
+
+        bits + usize::from(num_bits)
+    }
+}
+
+impl<'a, F, O> CompressContext<'a, F, O>
+where
+    F: Fn(u16) -> u16,
+    O: CharOutput,
+{
+    /// Make a new [`CompressContext`] that writes its chars to `output`.
+    ///
+    /// # Panics
+    /// Panics if `bits_per_char` exceeds the number of bits in a u16.
+    #[inline]
+    pub fn with_output(input: &'a [u16], output: O, bits_per_char: u8, to_char: F) -> Self {
+        Self::with_hasher(
+            input,
+            output,
+            bits_per_char,
+            to_char,
+            DefaultHashBuilder::default(),
+        )
+    }
+}
 
+impl<'a, F, O, S> CompressContext<'a, F, O, S>
+where
+    F: Fn(u16) -> u16,
+    O: CharOutput,
+    S: BuildHasher + Clone,
+{
+    /// Make a new [`CompressContext`] that writes its chars to `output`,
+    /// with a dictionary that uses the given hasher.
+    ///
+    /// # Panics
+    /// Panics if `bits_per_char` exceeds the number of bits in a u16.
+    #[inline]
+    pub fn with_hasher(
+        input: &'a [u16],
+        output: O,
+        bits_per_char: u8,
+        to_char: F,
+        hash_builder: S,
+    ) -> Self {
         CompressContext {
-            dictionary: HashMap::default(),
-            dictionary_to_create: HashSet::default(),
+            dictionary: HashMap::with_hasher(hash_builder.clone()),
+            dictionary_to_create: HashSet::with_hasher(hash_builder),
 
             w_start_idx: 0,
             w_end_idx: 0,
@@ -92,15 +171,40 @@ where
             enlarge_in: 2,
 
             input,
-            output: Vec::with_capacity(input.len() >> 1), // Lowball, assume we can get a 50% reduction in size.
-
-            bit_buffer: 0,
 
             num_bits: START_CODE_BITS,
 
-            bit_position: 0,
-            bits_per_char,
-            to_char,
+            writer: BitWriter::with_output(output, bits_per_char, to_char),
+        }
+    }
+
+    /// Make a [`CompressContext`] that continues from a point in the middle of the input.
+    ///
+    /// `w` is the current word and `enlarge_in` and `num_bits` must be the values before it is produced.
+    #[inline]
+    pub fn from_parts(
+        input: &'a [u16],
+        dictionary: HashMap<&'a [u16], u32, S>,
+        dictionary_to_create: HashSet<u16, S>,
+        w: core::ops::Range<usize>,
+        enlarge_in: u64,
+        num_bits: u8,
+        writer: BitWriter<F, O>,
+    ) -> Self {
+        CompressContext {
+            dictionary,
+            dictionary_to_create,
+
+            w_start_idx: w.start,
+            w_end_idx: w.end,
+
+            enlarge_in,
+
+            input,
+
+            num_bits,
+
+            writer,
         }
     }
 
@@ -129,27 +233,9 @@ where
         self.decrement_enlarge_in();
     }
 
-    /// Append the bit to the bit buffer.
-    #[inline]
-    pub fn write_bit(&mut self, bit: bool) {
-        self.bit_buffer = (self.bit_buffer << 1) | u16::from(bit);
-        self.bit_position += 1;
-
-        if self.bit_position == self.bits_per_char {
-            self.bit_position = 0;
-            let output_char = (self.to_char)(self.bit_buffer);
-            self.bit_buffer = 0;
-
-            self.output.push(output_char);
-        }
-    }
-
     #[inline]
-    pub fn write_bits(&mut self, n: u8, mut value: u32) {
-        for _ in 0..n {
-            self.write_bit(value & 1 == 1);
-            value >>= 1;
-        }
+    pub fn write_bits(&mut self, n: u8, value: u32) {
+        self.writer.write_bits(n, value);
     }
 
     #[inline]
@@ -167,8 +253,12 @@ where
         let c = &self.input[i];
 
         let dictionary_len = self.dictionary.len();
-        if let HashMapEntry::Vacant(entry) = self.dictionary.entry(std::slice::from_ref(c)) {
-            entry.insert((dictionary_len + NUM_BASE_CODES).try_into().unwrap());
+        if let HashMapEntry::Vacant(entry) = self.dictionary.entry(core::slice::from_ref(c)) {
+            entry.insert(
+                (dictionary_len + usize::from(NUM_BASE_CODES))
+                    .try_into()
+                    .unwrap(),
+            );
             self.dictionary_to_create.insert(*c);
         }
 
@@ -183,7 +273,11 @@ where
             }
             HashMapEntry::Vacant(entry) => {
                 // Add wc to the dictionary.
-                entry.insert((dictionary_len + NUM_BASE_CODES).try_into().unwrap());
+                entry.insert(
+                    (dictionary_len + usize::from(NUM_BASE_CODES))
+                        .try_into()
+                        .unwrap(),
+                );
 
                 // Originally, this was before adding wc to the dict.
                 // However, we only use the dict for a lookup that will crash if it fails in produce_w.
@@ -199,7 +293,7 @@ where
 
     /// Finish the stream and get the final result.
     #[inline]
-    pub fn finish(mut self) -> Vec<u16> {
+    pub fn finish(mut self) -> O {
         let w = &self.input[self.w_start_idx..self.w_end_idx];
 
         // Output the code for w.
@@ -210,17 +304,11 @@ where
         // Mark the end of the stream
         self.write_bits(self.num_bits, CLOSE_CODE.into());
 
-        let str_len = self.output.len();
-        // Flush the last char
-        while self.output.len() == str_len {
-            self.write_bit(false);
-        }
-
-        self.output
+        self.writer.finish()
     }
 
     /// Perform the compression and return the result.
-    pub fn compress(mut self) -> Vec<u16> {
+    pub fn compress(mut self) -> O {
         for i in 0..self.input.len() {
             self.write_u16(i);
         }
@@ -228,24 +316,259 @@ where
     }
 }
 
+/// How hard the compressor should look for a good encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CompressionLevel {
+    /// Always take the longest match.
+    ///
+    /// The output is identical to the reference implementation.
+    #[default]
+    Default,
+
+    /// Also try choosing matches so that the following match is as long as possible,
+    /// keeping whichever result is smallest.
+    ///
+    /// This is several times slower and the output may differ from the reference implementation,
+    /// but it is never larger and can still be decompressed by any lz-string decoder.
+    Best,
+}
+
+/// Where a [`BitWriter`] puts the chars it packs.
+pub(crate) trait CharOutput {
+    /// Make an empty output with room for `capacity` chars.
+    fn with_char_capacity(capacity: usize) -> Self;
+
+    /// Append a char.
+    fn push_char(&mut self, c: u16);
+}
+
+impl CharOutput for Vec<u16> {
+    #[inline]
+    fn with_char_capacity(capacity: usize) -> Self {
+        Vec::with_capacity(capacity)
+    }
+
+    #[inline]
+    fn push_char(&mut self, c: u16) {
+        self.push(c);
+    }
+}
+
+/// The chars are stored as UTF-8.
+///
+/// This is only used for encodings that never produce surrogates.
+impl CharOutput for String {
+    /// Most chars take 3 bytes as UTF-8.
+    #[inline]
+    fn with_char_capacity(capacity: usize) -> Self {
+        String::with_capacity(capacity.saturating_mul(3))
+    }
+
+    #[inline]
+    fn push_char(&mut self, c: u16) {
+        self.push(char::from_u32(c.into()).expect("encoded chars are never surrogates"));
+    }
+}
+
+/// The chars are stored as big endian bytes, like the output of [`compress_to_uint8_array`].
+impl CharOutput for Vec<u8> {
+    #[inline]
+    fn with_char_capacity(capacity: usize) -> Self {
+        Vec::with_capacity(capacity.saturating_mul(2))
+    }
+
+    #[inline]
+    fn push_char(&mut self, c: u16) {
+        self.extend_from_slice(&c.to_be_bytes());
+    }
+}
+
+/// The values of an [`Encoding`] with an ASCII alphabet, one byte each, before they are mapped to chars.
+pub(crate) struct ValueBytes(Vec<u8>);
+
+impl CharOutput for ValueBytes {
+    #[inline]
+    fn with_char_capacity(capacity: usize) -> Self {
+        ValueBytes(Vec::with_capacity(capacity))
+    }
+
+    #[inline]
+    #[allow(clippy::cast_possible_truncation)]
+    fn push_char(&mut self, c: u16) {
+        // Values have at most 6 bits.
+        self.0.push(c as u8);
+    }
+}
+
+/// The chars are kept inline until there are too many, for [`compress_small`].
+impl<const N: usize> CharOutput for InlineVec<u16, N> {
+    #[inline]
+    fn with_char_capacity(_capacity: usize) -> Self {
+        InlineVec::new()
+    }
+
+    #[inline]
+    fn push_char(&mut self, c: u16) {
+        self.push(c);
+    }
+}
+
+/// A packer for codes of variable bit widths.
+#[derive(Debug)]
+pub(crate) struct BitWriter<F, O = Vec<u16>> {
+    /// The output buffer.
+    output: O,
+
+    /// The bit buffer.
+    bit_buffer: u16,
+
+    /// The current bit position.
+    bit_position: u8,
+
+    /// The maximum # of bits per char.
+    ///
+    /// This value may not exceed 16,
+    /// as the reference implementation will also not handle values over 16.
+    bits_per_char: u8,
+
+    /// A transformation function to map a u16 to another u16,
+    /// before appending it to the output buffer.
+    to_char: F,
+}
+
+impl<F> BitWriter<F>
+where
+    F: Fn(u16) -> u16,
+{
+    /// Make a new [`BitWriter`].
+    ///
+    /// # Panics
+    /// Panics if `bits_per_char` exceeds the number of bits in a u16.
+    #[inline]
+    pub fn new(capacity: usize, bits_per_char: u8, to_char: F) -> Self {
+        Self::with_output(Vec::with_capacity(capacity), bits_per_char, to_char)
+    }
+
+    /// Make a [`BitWriter`] that continues after the given number of bits of already packed chars.
+    ///
+    /// # Panics
+    /// Panics if `bits_per_char` exceeds the number of bits in a u16,
+    /// or if there are not enough chars.
+    #[inline]
+    pub fn resume(values: &[u16], num_bits: usize, bits_per_char: u8, to_char: F) -> Self {
+        let mut writer = Self::new(values.len(), bits_per_char, to_char);
+
+        let bits_per_char_usize = usize::from(bits_per_char);
+        let full_chars = num_bits / bits_per_char_usize;
+        for &value in &values[..full_chars] {
+            let output_char = (writer.to_char)(value);
+            writer.output.push(output_char);
+        }
+
+        // This is less than bits_per_char.
+        let bit_position = u8::try_from(num_bits % bits_per_char_usize).unwrap();
+        if bit_position != 0 {
+            writer.bit_buffer = values[full_chars] >> (bits_per_char - bit_position);
+            writer.bit_position = bit_position;
+        }
+
+        writer
+    }
+
+    /// The number of bits that were written.
+    #[inline]
+    pub fn bits_written(&self) -> usize {
+        self.output.len() * usize::from(self.bits_per_char) + usize::from(self.bit_position)
+    }
+
+    /// Move the chars that are complete to the end of `output`.
+    ///
+    /// [`BitWriter::bits_written`] does not count the chars that were taken.
+    #[inline]
+    pub fn take_output(&mut self, output: &mut Vec<u16>) {
+        output.append(&mut self.output);
+    }
+}
+
+impl<F, O> BitWriter<F, O>
+where
+    F: Fn(u16) -> u16,
+    O: CharOutput,
+{
+    /// Make a new [`BitWriter`] that appends its chars to `output`.
+    ///
+    /// # Panics
+    /// Panics if `bits_per_char` exceeds the number of bits in a u16.
+    #[inline]
+    pub fn with_output(output: O, bits_per_char: u8, to_char: F) -> Self {
+        assert!(usize::from(bits_per_char) <= core::mem::size_of::<u16>() * 8);
+
+        BitWriter {
+            output,
+            bit_buffer: 0,
+            bit_position: 0,
+            bits_per_char,
+            to_char,
+        }
+    }
+
+    /// Append the bit to the bit buffer.
+    #[inline]
+    pub fn write_bit(&mut self, bit: bool) {
+        self.bit_buffer = (self.bit_buffer << 1) | u16::from(bit);
+        self.bit_position += 1;
+
+        if self.bit_position == self.bits_per_char {
+            self.bit_position = 0;
+            let output_char = (self.to_char)(self.bit_buffer);
+            self.bit_buffer = 0;
+
+            self.output.push_char(output_char);
+        }
+    }
+
+    #[inline]
+    pub fn write_bits(&mut self, n: u8, mut value: u32) {
+        for _ in 0..n {
+            self.write_bit(value & 1 == 1);
+            value >>= 1;
+        }
+    }
+
+    /// Flush the last char, padding it with at least one zero bit.
+    #[inline]
+    pub fn pad_last_char(&mut self) {
+        self.write_bit(false);
+        while self.bit_position != 0 {
+            self.write_bit(false);
+        }
+    }
+
+    /// Flush the last char and get the final result.
+    #[inline]
+    pub fn finish(mut self) -> O {
+        self.pad_last_char();
+        self.output
+    }
+}
+
 /// Compress a string into a [`Vec<u16>`].
 ///
 /// The resulting [`Vec`] may contain invalid UTF16.
 #[inline]
 pub fn compress(data: impl IntoWideIter) -> Vec<u16> {
-    let data: Vec<u16> = data.into_wide_iter().collect();
-    compress_internal(&data, 16, std::convert::identity)
+    let data: InlineVec<u16, SMALL_LEN> = data.into_wide_iter().collect();
+    compress_internal(data.as_slice(), 16, core::convert::identity)
 }
 
 /// Compress a string as a valid [`String`].
 ///
-/// This function converts the result back into a Rust [`String`] since it is guaranteed to be valid UTF16.
+/// The chars are written straight to the [`String`], as the output is guaranteed to be valid UTF16.
 #[inline]
 pub fn compress_to_utf16(data: impl IntoWideIter) -> String {
-    let data: Vec<u16> = data.into_wide_iter().collect();
-    let compressed = compress_internal(&data, 15, |n| n + 32);
-    let mut compressed =
-        String::from_utf16(&compressed).expect("`compress_to_utf16 output was not valid unicode`");
+    let data: InlineVec<u16, SMALL_LEN> = data.into_wide_iter().collect();
+
+    let mut compressed: String = compress_to_output(data.as_slice(), Encoding::Utf16);
     compressed.push(' ');
 
     compressed
@@ -253,40 +576,128 @@ pub fn compress_to_utf16(data: impl IntoWideIter) -> String {
 
 /// Compress a string into a [`String`], which can be safely used in a uri.
 ///
-/// This function converts the result back into a Rust [`String`] since it is guaranteed to be valid unicode.
+/// The chars are written straight to the [`String`], as the output is guaranteed to be valid unicode.
 #[inline]
 pub fn compress_to_encoded_uri_component(data: impl IntoWideIter) -> String {
-    let data: Vec<u16> = data.into_wide_iter().collect();
-    let compressed = compress_internal(&data, 6, |n| u16::from(URI_KEY[usize::from(n)]));
+    let data: InlineVec<u16, SMALL_LEN> = data.into_wide_iter().collect();
 
-    String::from_utf16(&compressed)
-        .expect("`compress_to_encoded_uri_component` output was not valid unicode`")
+    compress_to_ascii(data.as_slice(), Encoding::EncodedUriComponent)
 }
 
 /// Compress a string into a [`String`], which is valid base64.
 ///
-/// This function converts the result back into a Rust [`String`] since it is guaranteed to be valid unicode.
+/// The chars are written straight to the [`String`], as the output is guaranteed to be valid unicode.
 pub fn compress_to_base64(data: impl IntoWideIter) -> String {
-    let data: Vec<u16> = data.into_wide_iter().collect();
-    let mut compressed = compress_internal(&data, 6, |n| u16::from(BASE64_KEY[usize::from(n)]));
+    let data: InlineVec<u16, SMALL_LEN> = data.into_wide_iter().collect();
 
-    let mod_4 = compressed.len() % 4;
+    let mut compressed = compress_to_ascii(data.as_slice(), Encoding::Base64);
 
-    if mod_4 != 0 {
-        for _ in mod_4..(4 + 1) {
-            compressed.push(u16::from(b'='));
-        }
-    }
+    // The chars are ASCII, so there is one byte per char.
+    let len = compressed.len();
+    let padded_len = Encoding::Base64.padded_len(len);
+    compressed.extend((len..padded_len).map(|_| '='));
 
-    String::from_utf16(&compressed).expect("`compress_to_base64` output was not valid unicode`")
+    compressed
 }
 
 /// Compress a string into a [`Vec<u8>`].
 pub fn compress_to_uint8_array(data: impl IntoWideIter) -> Vec<u8> {
-    compress(data)
-        .into_iter()
-        .flat_map(|value| value.to_be_bytes())
-        .collect()
+    let data: InlineVec<u16, SMALL_LEN> = data.into_wide_iter().collect();
+
+    compress_to_output(data.as_slice(), Encoding::Raw)
+}
+
+/// Compress data into the chars of the given [`Encoding`], written straight to the output.
+///
+/// This does not add the padding, but leaves room for it.
+#[inline]
+fn compress_to_output<O>(data: &[u16], encoding: Encoding) -> O
+where
+    O: CharOutput,
+{
+    compress_to(
+        data,
+        encoding.bits_per_char(),
+        |n| encoding.value_to_char(n),
+        |len| encoding.padded_len(len),
+    )
+}
+
+/// Compress data into the chars of an [`Encoding`] with an ASCII alphabet.
+///
+/// With SIMD, the values are packed first and then mapped to their chars all at once.
+/// Otherwise, each char is mapped as it is written.
+/// This does not add the padding.
+#[inline]
+fn compress_to_ascii(data: &[u16], encoding: Encoding) -> String {
+    if !has_simd() {
+        return compress_to_output(data, encoding);
+    }
+
+    let ValueBytes(mut compressed) = compress_to(
+        data,
+        encoding.bits_per_char(),
+        core::convert::identity,
+        |len| encoding.padded_len(len),
+    );
+    values_to_ascii(&mut compressed, encoding);
+
+    String::from_utf8(compressed).expect("the alphabet is ASCII")
+}
+
+/// Compress data into chars written straight to an output of type `O`.
+///
+/// Inputs of at most [`SMALL_LEN`] chars are compressed on the stack,
+/// so only the output is allocated, with room for exactly `padded_len` of its chars.
+#[inline]
+fn compress_to<O, F, P>(data: &[u16], bits_per_char: u8, to_char: F, padded_len: P) -> O
+where
+    O: CharOutput,
+    F: Fn(u16) -> u16,
+    P: Fn(usize) -> usize,
+{
+    compress_to_with_hasher(
+        data,
+        bits_per_char,
+        to_char,
+        padded_len,
+        DefaultHashBuilder::default,
+    )
+}
+
+/// Compress data into chars written straight to an output of type `O`,
+/// with a dictionary that uses the hasher from `hash_builder`.
+///
+/// Small inputs whose entries collide too much on the stack fall back to that dictionary too.
+/// The hasher is only made if it is needed.
+#[inline]
+fn compress_to_with_hasher<O, F, P, S>(
+    data: &[u16],
+    bits_per_char: u8,
+    to_char: F,
+    padded_len: P,
+    hash_builder: impl FnOnce() -> S,
+) -> O
+where
+    O: CharOutput,
+    F: Fn(u16) -> u16,
+    P: Fn(usize) -> usize,
+    S: BuildHasher + Clone,
+{
+    if data.len() <= SMALL_LEN {
+        if let Some(chars) = compress_small(data, bits_per_char, &to_char) {
+            let mut output = O::with_char_capacity(padded_len(chars.len()));
+            for &c in chars.as_slice() {
+                output.push_char(c);
+            }
+            return output;
+        }
+    }
+
+    // Lowball, like `CompressContext::new`, assuming a 50% reduction in size.
+    let capacity = data.len().saturating_mul(8) / usize::from(bits_per_char);
+    let output = O::with_char_capacity(padded_len(capacity));
+    CompressContext::with_hasher(data, output, bits_per_char, to_char, hash_builder()).compress()
 }
 
 /// The internal function for compressing data.
@@ -298,6 +709,270 @@ pub fn compress_internal<F>(data: &[u16], bits_per_char: u8, to_char: F) -> Vec<
 where
     F: Fn(u16) -> u16,
 {
-    let ctx = CompressContext::new(data, bits_per_char, to_char);
-    ctx.compress()
+    compress_to(data, bits_per_char, to_char, core::convert::identity)
+}
+
+/// The internal function for compressing data with a dictionary that uses a given hasher.
+///
+/// This is the same as [`compress_internal`],
+/// except that the dictionary hashes its entries with `hash_builder`.
+/// Small inputs are still compressed on the stack with a fixed hash,
+/// unless their entries collide too much, in which case `hash_builder` is used for them as well.
+#[inline]
+pub fn compress_internal_with_hasher<F, S>(
+    data: &[u16],
+    bits_per_char: u8,
+    to_char: F,
+    hash_builder: S,
+) -> Vec<u16>
+where
+    F: Fn(u16) -> u16,
+    S: BuildHasher + Clone,
+{
+    compress_to_with_hasher(
+        data,
+        bits_per_char,
+        to_char,
+        core::convert::identity,
+        || hash_builder,
+    )
+}
+
+/// The hasher of the compressor's dictionary, for [`compress_with_hasher`].
+///
+/// The output is the same with every hasher; only the speed and the resistance to HashDoS differ.
+///
+/// Inputs of up to 256 chars (64 without `std`) do not use the chosen hasher.
+/// They are compressed on the stack with a fixed hash,
+/// which gives up after a bounded number of collisions and then falls back to the chosen hasher,
+/// so crafted inputs cannot make them slow either.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum DictionaryHasher {
+    /// The hasher used by the other compress functions.
+    ///
+    /// This is `FxHash` with the `rustc-hash` feature,
+    /// `SipHash` with `std`,
+    /// and the default hasher of `hashbrown` otherwise.
+    #[default]
+    Default,
+
+    /// The randomly keyed SipHash of [`std::collections::hash_map::RandomState`].
+    ///
+    /// It is slower, but resists HashDoS, so it is a good choice for large untrusted inputs.
+    #[cfg(feature = "std")]
+    SipHash,
+
+    /// The FxHash of `rustc-hash`.
+    ///
+    /// It is the fastest, but inputs can be crafted to make it slow.
+    #[cfg(feature = "rustc-hash")]
+    FxHash,
+
+    /// The randomly keyed aHash of `ahash`.
+    ///
+    /// It is almost as fast as FxHash and also resists HashDoS.
+    #[cfg(feature = "ahash")]
+    AHash,
+}
+
+/// Compress data into the chars of the given [`Encoding`],
+/// with a dictionary that uses the given [`DictionaryHasher`].
+///
+/// The output includes the padding of the encoding,
+/// and is identical to the output of the compress function for the encoding, as `u16`s.
+///
+/// Like the other compress functions, small inputs are compressed on the stack with a fixed hash
+/// and only use `hasher` if too many of their entries collide.
+/// See [`DictionaryHasher`] for details.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::{DictionaryHasher, Encoding};
+/// let data = "The quick brown fox jumps over the lazy dog";
+/// let compressed = lz_str::compress_with_hasher(data, Encoding::Base64, DictionaryHasher::Default);
+///
+/// assert_eq!(String::from_utf16(&compressed).unwrap(), lz_str::compress_to_base64(data));
+/// ```
+pub fn compress_with_hasher(
+    data: impl IntoWideIter,
+    encoding: Encoding,
+    hasher: DictionaryHasher,
+) -> Vec<u16> {
+    let data: InlineVec<u16, SMALL_LEN> = data.into_wide_iter().collect();
+    let bits_per_char = encoding.bits_per_char();
+    let to_char = |n| encoding.value_to_char(n);
+
+    let mut compressed = match hasher {
+        DictionaryHasher::Default => compress_internal_with_hasher(
+            data.as_slice(),
+            bits_per_char,
+            to_char,
+            DefaultHashBuilder::default(),
+        ),
+        #[cfg(feature = "std")]
+        DictionaryHasher::SipHash => compress_internal_with_hasher(
+            data.as_slice(),
+            bits_per_char,
+            to_char,
+            std::collections::hash_map::RandomState::new(),
+        ),
+        #[cfg(feature = "rustc-hash")]
+        DictionaryHasher::FxHash => compress_internal_with_hasher(
+            data.as_slice(),
+            bits_per_char,
+            to_char,
+            core::hash::BuildHasherDefault::<rustc_hash::FxHasher>::default(),
+        ),
+        #[cfg(feature = "ahash")]
+        DictionaryHasher::AHash => compress_internal_with_hasher(
+            data.as_slice(),
+            bits_per_char,
+            to_char,
+            ahash::RandomState::new(),
+        ),
+    };
+    encoding.pad(&mut compressed);
+
+    compressed
+}
+
+/// Compress data into the chars of the given [`Encoding`], including its padding.
+#[inline]
+pub(crate) fn compress_with_encoding(data: &[u16], encoding: Encoding) -> Vec<u16> {
+    let mut compressed = compress_internal(data, encoding.bits_per_char(), |n| {
+        encoding.value_to_char(n)
+    });
+    encoding.pad(&mut compressed);
+
+    compressed
+}
+
+/// Where the input may be cut by [`compress_bounded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Boundary {
+    /// After any `u16`.
+    #[default]
+    CodeUnit,
+
+    /// After any char, so surrogate pairs are never split.
+    Char,
+
+    /// After a '\n', or at the end of the input.
+    Line,
+}
+
+impl Boundary {
+    /// Whether the input may be cut before the `u16` at `i`.
+    #[inline]
+    fn allows(self, data: &[u16], i: usize) -> bool {
+        if i == 0 || i == data.len() {
+            return true;
+        }
+
+        match self {
+            Self::CodeUnit => true,
+            Self::Char => {
+                !((0xD800..0xDC00).contains(&data[i - 1]) && (0xDC00..0xE000).contains(&data[i]))
+            }
+            Self::Line => data[i - 1] == u16::from(b'\n'),
+        }
+    }
+}
+
+/// Compress the longest prefix of the input whose output fits in `max_len` chars.
+///
+/// The output is in the chars of the given [`Encoding`], including its padding,
+/// and is the same as compressing the prefix with the encoding's compress function.
+/// Only one pass over the input is needed,
+/// as the size of the output is known after every char.
+///
+/// Returns the output and the number of `u16`s of the input that were compressed.
+///
+/// # Errors
+/// Returns `None` if not even an empty input fits.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::{Boundary, Encoding};
+/// let data = "first line\nsecond line\nthird line\n";
+/// let (compressed, consumed) = lz_str::compress_bounded(data, Encoding::EncodedUriComponent, 30, Boundary::Line)
+///     .expect("too small");
+///
+/// assert!(compressed.len() <= 30);
+/// assert_eq!(&data[..consumed], "first line\n");
+/// ```
+pub fn compress_bounded(
+    data: impl IntoWideIter,
+    encoding: Encoding,
+    max_len: usize,
+    boundary: Boundary,
+) -> Option<(Vec<u16>, usize)> {
+    let data: Vec<u16> = data.into_wide_iter().collect();
+    let bits_per_char = usize::from(encoding.bits_per_char());
+
+    // The writer pads the last char with at least one bit.
+    let fits = |bits: usize| encoding.padded_len(bits / bits_per_char + 1) <= max_len;
+
+    let mut ctx = CompressContext::new(&data, encoding.bits_per_char(), |n| {
+        encoding.value_to_char(n)
+    });
+    if !fits(ctx.finished_bits()) {
+        return None;
+    }
+
+    let mut consumed = 0;
+    for i in 0..data.len() {
+        ctx.write_u16(i);
+
+        // The bits that were written never change,
+        // so no longer prefix can fit.
+        // The padding is left out, as it is not monotonic for base64.
+        if ctx.writer.bits_written() / bits_per_char + 1 > max_len {
+            break;
+        }
+
+        if boundary.allows(&data, i + 1) && fits(ctx.finished_bits()) {
+            consumed = i + 1;
+        }
+    }
+
+    Some((
+        compress_with_encoding(&data[..consumed], encoding),
+        consumed,
+    ))
+}
+
+/// The internal function for compressing data with a given [`CompressionLevel`].
+///
+/// This is the same as [`compress_internal`],
+/// except that it allows choosing how the input is split into codes.
+pub fn compress_internal_with_level<F>(
+    data: &[u16],
+    bits_per_char: u8,
+    to_char: F,
+    level: CompressionLevel,
+) -> Vec<u16>
+where
+    F: Fn(u16) -> u16,
+{
+    match level {
+        CompressionLevel::Default => compress_internal(data, bits_per_char, to_char),
+        CompressionLevel::Best => {
+            let mut best = compress_internal(data, bits_per_char, &to_char);
+
+            // Which minimum gain works best depends on the input,
+            // so just try a few.
+            for min_gain in 2..=4 {
+                let compressed =
+                    encode_tokens(flexible_parse(data, min_gain), bits_per_char, &to_char)
+                        .expect("flexible parsing produced an invalid reference");
+                if compressed.len() < best.len() {
+                    best = compressed;
+                }
+            }
+
+            best
+        }
+    }
 }
@@ -1,6 +1,34 @@
 pub const URI_KEY: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+-$";
 pub const BASE64_KEY: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=";
 
+/// The entry of a reverse lookup table for a byte that is not part of the key.
+pub const NO_VALUE: u8 = u8::MAX;
+
+/// The value of each byte in [`URI_KEY`], or [`NO_VALUE`].
+///
+/// Spaces are 62 as well, as they may have been decoded from a '+'.
+pub const URI_VALUES: [u8; 256] = {
+    let mut values = reverse_key(URI_KEY);
+    values[b' ' as usize] = 62;
+    values
+};
+
+/// The value of each byte in [`BASE64_KEY`], or [`NO_VALUE`].
+pub const BASE64_VALUES: [u8; 256] = reverse_key(BASE64_KEY);
+
+/// Make a reverse lookup table for a key of at most 255 bytes.
+// `From` cannot be used in a const fn.
+#[allow(clippy::cast_lossless, clippy::cast_possible_truncation)]
+const fn reverse_key(key: &[u8]) -> [u8; 256] {
+    let mut values = [NO_VALUE; 256];
+    let mut i = 0;
+    while i < key.len() {
+        values[key[i] as usize] = i as u8;
+        i += 1;
+    }
+    values
+}
+
 /// The stream code for a `u8`.
 pub const U8_CODE: u8 = 0;
 
@@ -10,6 +38,12 @@ pub const U16_CODE: u8 = 1;
 /// End of stream signal
 pub const CLOSE_CODE: u8 = 2;
 
+/// The number of "base codes",
+/// the default codes of all streams.
+///
+/// These are U8_CODE, U16_CODE, and CLOSE_CODE.
+pub const NUM_BASE_CODES: u8 = 3;
+
 /// The starting size of a code.
 ///
 /// Compression starts with the following codes:
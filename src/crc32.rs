@@ -0,0 +1,74 @@
+/// The reversed CRC-32 polynomial, as used by zlib and PNG.
+const POLYNOMIAL: u32 = 0xEDB8_8320;
+
+/// The CRC of every byte.
+const TABLE: [u32; 256] = make_table();
+
+const fn make_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i: u32 = 0;
+    while i < 256 {
+        let mut crc = i;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i as usize] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// A CRC-32 checksum, computed incrementally.
+#[derive(Debug, Clone)]
+pub(crate) struct Crc32 {
+    crc: u32,
+}
+
+impl Crc32 {
+    /// Make a new [`Crc32`].
+    #[inline]
+    pub fn new() -> Self {
+        Crc32 { crc: u32::MAX }
+    }
+
+    /// Add bytes to the checksum.
+    #[inline]
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            let i = (self.crc ^ u32::from(byte)) & 0xFF;
+            self.crc = (self.crc >> 8) ^ TABLE[i as usize];
+        }
+    }
+
+    /// Add wide chars to the checksum, as little endian bytes.
+    #[inline]
+    pub fn update_wide(&mut self, data: &[u16]) {
+        for c in data {
+            self.update(&c.to_le_bytes());
+        }
+    }
+
+    /// Get the checksum.
+    #[inline]
+    pub fn finish(&self) -> u32 {
+        !self.crc
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn crc32_check_value() {
+        let mut crc = Crc32::new();
+        crc.update(b"123456789");
+        assert_eq!(crc.finish(), 0xCBF4_3926);
+    }
+}
@@ -1,12 +1,34 @@
-use crate::constants::BASE64_KEY;
-use crate::constants::CLOSE_CODE;
-use crate::constants::START_CODE_BITS;
-use crate::constants::U16_CODE;
-use crate::constants::U8_CODE;
-use crate::constants::URI_KEY;
+#[cfg(feature = "alloc")]
+use crate::alphabet::AsciiValues;
+#[cfg(feature = "alloc")]
+use crate::constants::NUM_BASE_CODES;
+#[cfg(feature = "alloc")]
+use crate::encoding::Encoding;
+#[cfg(feature = "alloc")]
+use crate::fixed::DictionaryEntry;
+#[cfg(feature = "alloc")]
+use crate::small::InlineVec;
+#[cfg(feature = "alloc")]
+use crate::small::SMALL_ENTRIES;
+#[cfg(feature = "alloc")]
+use crate::small::SMALL_LEN;
+#[cfg(feature = "alloc")]
+use crate::token::Token;
+#[cfg(feature = "alloc")]
+use crate::token::TokenError;
+#[cfg(feature = "alloc")]
+use crate::token::Tokenizer;
+#[cfg(feature = "alloc")]
 use crate::IntoWideIter;
-use std::convert::TryFrom;
-use std::convert::TryInto;
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+#[cfg(feature = "alloc")]
+use core::convert::TryInto;
+#[cfg(feature = "alloc")]
+use core::ops::Range;
 
 #[derive(Debug)]
 pub struct DecompressContext<I> {
@@ -14,6 +36,9 @@ pub struct DecompressContext<I> {
     compressed_data: I,
     position: u16,
     reset_val: u16,
+
+    /// The index of `val` in the compressed data.
+    index: usize,
 }
 
 impl<I> DecompressContext<I>
@@ -29,7 +54,7 @@ where
     /// Panics if `bits_per_char` is greater than the number of bits in a `u16`.
     #[inline]
     pub fn new(mut compressed_data: I, bits_per_char: u8) -> Option<Self> {
-        assert!(usize::from(bits_per_char) <= std::mem::size_of::<u16>() * 8);
+        assert!(usize::from(bits_per_char) <= core::mem::size_of::<u16>() * 8);
 
         let reset_val_pow = bits_per_char - 1;
         // (1 << 15) <= u16::MAX
@@ -40,6 +65,7 @@ where
             compressed_data,
             position: reset_val,
             reset_val,
+            index: 0,
         })
     }
 
@@ -51,11 +77,22 @@ where
         if self.position == 0 {
             self.position = self.reset_val;
             self.val = self.compressed_data.next()?;
+            self.index += 1;
         }
 
         Some(res != 0)
     }
 
+    /// The number of bits that were read.
+    #[inline]
+    pub fn bit_position(&self) -> usize {
+        let bits_per_char = self.reset_val.trailing_zeros() + 1;
+        let bits_read = self.reset_val.trailing_zeros() - self.position.trailing_zeros();
+
+        // Both are at most 16.
+        self.index * usize::try_from(bits_per_char).unwrap() + usize::try_from(bits_read).unwrap()
+    }
+
     /// Read n bits.
     ///
     /// `u32` is the return type as we expect all possible codes to be within that type's range.
@@ -78,6 +115,7 @@ where
 ///
 /// # Errors
 /// Returns `None` if the decompression fails.
+#[cfg(feature = "alloc")]
 #[inline]
 pub fn decompress(compressed: impl IntoWideIter) -> Option<Vec<u16>> {
     decompress_internal(compressed.into_wide_iter(), 16)
@@ -87,61 +125,37 @@ pub fn decompress(compressed: impl IntoWideIter) -> Option<Vec<u16>> {
 ///
 /// # Errors
 /// Returns an error if the compressed data could not be decompressed.
+#[cfg(feature = "alloc")]
 #[inline]
 pub fn decompress_from_utf16(compressed: &str) -> Option<Vec<u16>> {
-    decompress_internal(compressed.encode_utf16().map(|c| c - 32), 15)
+    decompress_with_encoding(compressed.encode_utf16(), Encoding::Utf16)
 }
 
 /// Decompress a [`&str`] compressed with [`crate::compress_to_encoded_uri_component`].
 ///
 /// # Errors
 /// Returns an error if the compressed data could not be decompressed.
+#[cfg(feature = "alloc")]
 #[inline]
 pub fn decompress_from_encoded_uri_component(compressed: &str) -> Option<Vec<u16>> {
-    let compressed: Option<Vec<u16>> = compressed
-        .encode_utf16()
-        .map(|c| {
-            if c == u16::from(b' ') {
-                u16::from(b'+')
-            } else {
-                c
-            }
-        })
-        .map(u32::from)
-        .flat_map(|c| {
-            URI_KEY
-                .iter()
-                .position(|k| u8::try_from(c) == Ok(*k))
-                .map(|n| u16::try_from(n).ok())
-        })
-        .collect();
-
-    decompress_internal(compressed?.into_iter(), 6)
+    decompress_ascii(compressed, Encoding::EncodedUriComponent)
 }
 
 /// Decompress a [`&str`] compressed with [`crate::compress_to_base64`].
 ///
 /// # Errors
 /// Returns an error if the compressed data could not be decompressed.
+#[cfg(feature = "alloc")]
 #[inline]
 pub fn decompress_from_base64(compressed: &str) -> Option<Vec<u16>> {
-    let compressed: Option<Vec<u16>> = compressed
-        .encode_utf16()
-        .flat_map(|c| {
-            BASE64_KEY
-                .iter()
-                .position(|k| u8::try_from(c) == Ok(*k))
-                .map(|n| u16::try_from(n).ok())
-        })
-        .collect();
-
-    decompress_internal(compressed?.into_iter(), 6)
+    decompress_ascii(compressed, Encoding::Base64)
 }
 
 /// Decompress a byte slice compressed with [`crate::compress_to_uint8_array`].
 ///
 /// # Errors
 /// Returns an error if the compressed data could not be decompressed.
+#[cfg(feature = "alloc")]
 #[inline]
 pub fn decompress_from_uint8_array(compressed: &[u8]) -> Option<Vec<u16>> {
     // The buffer is a UCS2 big endian encoded string.
@@ -151,16 +165,84 @@ pub fn decompress_from_uint8_array(compressed: &[u8]) -> Option<Vec<u16>> {
         return None;
     }
 
-    let buffer: Vec<u16> = compressed
-        .chunks(2)
-        .map(|slice| {
-            // The slice is always guaranteed to be 2 here.
-            // We check to see if the length is a multiple of 2 earlier.
-            u16::from_be_bytes(slice.try_into().unwrap())
-        })
-        .collect();
+    let compressed = compressed.chunks_exact(2).map(|slice| {
+        // The slice is always guaranteed to be 2 here.
+        u16::from_be_bytes(slice.try_into().unwrap())
+    });
+
+    decompress_internal(compressed, 16)
+}
+
+/// Decompress a [`&str`] of an encoding with an ASCII alphabet.
+///
+/// The bytes are mapped directly, in blocks, as only ASCII chars are part of the alphabet
+/// and the bytes of other chars are never ASCII.
+#[cfg(feature = "alloc")]
+#[inline]
+fn decompress_ascii(compressed: &str, encoding: Encoding) -> Option<Vec<u16>> {
+    let compressed = AsciiValues::new(compressed.as_bytes(), encoding);
+
+    decompress_internal(compressed, encoding.bits_per_char())
+}
+
+/// Decompress the chars of the given [`Encoding`].
+#[cfg(feature = "alloc")]
+#[inline]
+pub(crate) fn decompress_with_encoding<I>(compressed: I, encoding: Encoding) -> Option<Vec<u16>>
+where
+    I: Iterator<Item = u16>,
+{
+    decompress_internal(encoding.values(compressed), encoding.bits_per_char())
+}
+
+/// Get the length of the decompressed data, without decompressing it.
+///
+/// This only keeps track of the length of each dictionary entry,
+/// so it is much cheaper than [`decompress_internal`] and can be used to reject large inputs early.
+///
+/// # Errors
+/// Returns `None` if the compressed data could not be decompressed.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::Encoding;
+/// let compressed = lz_str::compress_to_base64("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+/// assert_eq!(lz_str::decompressed_len(Encoding::Base64, &compressed), Some(32));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn decompressed_len(encoding: Encoding, compressed: impl IntoWideIter) -> Option<usize> {
+    let compressed = encoding.values(compressed.into_wide_iter());
 
-    decompress(buffer)
+    let mut entry_lens: Vec<usize> = vec![1; usize::from(NUM_BASE_CODES)];
+    let mut w_len = 0;
+    let mut len: usize = 0;
+    for token in Tokenizer::new(compressed, encoding.bits_per_char()) {
+        let entry_len = match token.ok()? {
+            Token::U8(_) | Token::U16(_) => {
+                entry_lens.push(1);
+                1
+            }
+            // The tokenizer validated the code,
+            // so a missing entry is w+w[0].
+            Token::Reference(code) => entry_lens
+                .get(usize::try_from(code).ok()?)
+                .copied()
+                .unwrap_or(w_len + 1),
+            Token::Close => return Some(len),
+        };
+
+        len = len.checked_add(entry_len)?;
+
+        // Add w+entry[0] to the dictionary.
+        if w_len != 0 {
+            entry_lens.push(w_len + 1);
+        }
+
+        w_len = entry_len;
+    }
+
+    // Only an empty input has no close code.
+    Some(len)
 }
 
 /// The internal decompress function.
@@ -173,87 +255,153 @@ pub fn decompress_from_uint8_array(compressed: &[u8]) -> Option<Vec<u16>> {
 ///
 /// # Panics
 /// Panics if `bits_per_char` is greater than the number of bits in a `u16`.
+#[cfg(feature = "alloc")]
 #[inline]
 pub fn decompress_internal<I>(compressed: I, bits_per_char: u8) -> Option<Vec<u16>>
 where
     I: Iterator<Item = u16>,
 {
-    let mut ctx = match DecompressContext::new(compressed, bits_per_char) {
-        Some(ctx) => ctx,
-        None => return Some(Vec::new()),
-    };
-
-    let mut dictionary: Vec<Vec<u16>> = Vec::with_capacity(16);
-    for i in 0_u16..3_u16 {
-        dictionary.push(vec![i]);
+    let mut result: InlineVec<u16, SMALL_LEN> = InlineVec::new();
+    decompress_tokens(&mut Tokenizer::new(compressed, bits_per_char), &mut result)
+        .1
+        .ok()?;
+
+    Some(result.into_vec())
+}
+
+/// Where [`decompress_tokens`] puts the decompressed data.
+#[cfg(feature = "alloc")]
+pub(crate) trait DecompressedOutput {
+    /// The data written so far.
+    fn as_slice(&self) -> &[u16];
+
+    /// Append a char.
+    fn push(&mut self, c: u16);
+
+    /// Copy the chars in `range` to the end.
+    fn extend_from_within(&mut self, range: Range<usize>);
+}
+
+#[cfg(feature = "alloc")]
+impl DecompressedOutput for Vec<u16> {
+    #[inline]
+    fn as_slice(&self) -> &[u16] {
+        self
     }
 
-    // u8::MAX > u2::MAX
-    let code = u8::try_from(ctx.read_bits(START_CODE_BITS)?).unwrap();
-    let first_entry = match code {
-        U8_CODE | U16_CODE => {
-            let bits_to_read = (code * 8) + 8;
-            // bits_to_read == 8 or 16 <= 16
-            u16::try_from(ctx.read_bits(bits_to_read)?).unwrap()
-        }
-        CLOSE_CODE => return Some(Vec::new()),
-        _ => return None,
-    };
-    dictionary.push(vec![first_entry]);
-
-    let mut w = vec![first_entry];
-    let mut result = vec![first_entry];
-    let mut num_bits: u8 = 3;
-    let mut enlarge_in: u64 = 4;
-    let mut entry;
-    loop {
-        let mut code = ctx.read_bits(num_bits)?;
-        match u8::try_from(code) {
-            Ok(code_u8 @ (U8_CODE | U16_CODE)) => {
-                let bits_to_read = (code_u8 * 8) + 8;
-                // if cc == 0 {
-                // if (errorCount++ > 10000) return "Error"; // TODO: Error logic
-                // }
-
-                // bits_to_read == 8 or 16 <= 16
-                let bits = u16::try_from(ctx.read_bits(bits_to_read)?).unwrap();
-                dictionary.push(vec![bits]);
-                code = u32::try_from(dictionary.len() - 1).ok()?;
-                enlarge_in -= 1;
-            }
-            Ok(CLOSE_CODE) => return Some(result),
-            _ => {}
-        }
+    #[inline]
+    fn push(&mut self, c: u16) {
+        Vec::push(self, c);
+    }
 
-        if enlarge_in == 0 {
-            enlarge_in = 1 << num_bits;
-            num_bits += 1;
-        }
+    #[inline]
+    fn extend_from_within(&mut self, range: Range<usize>) {
+        Vec::extend_from_within(self, range);
+    }
+}
 
-        // Return error if code cannot be converted to dictionary index
-        let code_usize = usize::try_from(code).ok()?;
-        if let Some(entry_value) = dictionary.get(code_usize) {
-            entry = entry_value.clone();
-        } else if code_usize == dictionary.len() {
-            entry = w.clone();
-            entry.push(*w.first()?);
-        } else {
-            return None;
-        }
+/// The data is kept inline until there is too much, for [`decompress_internal`].
+#[cfg(feature = "alloc")]
+impl<const N: usize> DecompressedOutput for InlineVec<u16, N> {
+    #[inline]
+    fn as_slice(&self) -> &[u16] {
+        InlineVec::as_slice(self)
+    }
+
+    #[inline]
+    fn push(&mut self, c: u16) {
+        InlineVec::push(self, c);
+    }
 
-        result.extend(&entry);
+    #[inline]
+    fn extend_from_within(&mut self, range: Range<usize>) {
+        InlineVec::extend_from_within(self, range);
+    }
+}
 
-        // Add w+entry[0] to the dictionary.
-        let mut to_be_inserted = w.clone();
-        to_be_inserted.push(*entry.first()?);
-        dictionary.push(to_be_inserted);
-        enlarge_in -= 1;
+/// Decompress a code stream into `output`, stopping at the close code or the first error.
+///
+/// Returns the bit position of the last code that was read, along with the error if there was one.
+/// On error, `output` holds everything produced by the codes before the failing one.
+/// Afterwards, the tokenizer's bit position is the end of the last code.
+///
+/// Like [`crate::decompress_into`], dictionary entries are runs of the output,
+/// so small inputs do not allocate anything besides the output.
+#[cfg(feature = "alloc")]
+pub(crate) fn decompress_tokens<I, O>(
+    tokenizer: &mut Tokenizer<I>,
+    output: &mut O,
+) -> (usize, Result<(), TokenError>)
+where
+    I: Iterator<Item = u16>,
+    O: DecompressedOutput,
+{
+    // The entries after the base codes.
+    let mut dictionary: InlineVec<DictionaryEntry, SMALL_ENTRIES> = InlineVec::new();
 
-        w = entry;
+    // The previous entry, which is always right before the current one in the output.
+    let mut w = DictionaryEntry::EMPTY;
+    loop {
+        let bit_position = tokenizer.bit_position();
+        let token = match tokenizer.next() {
+            Some(Ok(token)) => token,
+            Some(Err(error)) => return (bit_position, Err(error)),
+            // Only an empty input has no close code.
+            None => return (bit_position, Ok(())),
+        };
+
+        let start = output.as_slice().len();
+        let entry = match token {
+            Token::U8(c) => {
+                output.push(c.into());
+                let entry = DictionaryEntry { start, len: 1 };
+                dictionary.push(entry);
+                entry
+            }
+            Token::U16(c) => {
+                output.push(c);
+                let entry = DictionaryEntry { start, len: 1 };
+                dictionary.push(entry);
+                entry
+            }
+            Token::Reference(code) => {
+                let entry = usize::try_from(code)
+                    .ok()
+                    .and_then(|code| code.checked_sub(usize::from(NUM_BASE_CODES)))
+                    .and_then(|index| dictionary.as_slice().get(index));
+                match entry {
+                    Some(entry) => {
+                        output.extend_from_within(entry.start..entry.start + entry.len);
+                        DictionaryEntry {
+                            start,
+                            len: entry.len,
+                        }
+                    }
+                    // The tokenizer validated the code,
+                    // so a missing entry is the one that is about to be created.
+                    None if w.len != 0 => {
+                        output.extend_from_within(w.start..w.start + w.len);
+                        output.push(output.as_slice()[w.start]);
+                        DictionaryEntry {
+                            start,
+                            len: w.len + 1,
+                        }
+                    }
+                    None => return (bit_position, Err(TokenError::InvalidReference(code))),
+                }
+            }
+            Token::Close => return (bit_position, Ok(())),
+        };
 
-        if enlarge_in == 0 {
-            enlarge_in = 1 << num_bits;
-            num_bits += 1;
+        // Add w+entry[0] to the dictionary.
+        // The entry follows w in the output, so this is a run of the output as well.
+        if w.len != 0 {
+            dictionary.push(DictionaryEntry {
+                start: w.start,
+                len: w.len + 1,
+            });
         }
+
+        w = entry;
     }
 }
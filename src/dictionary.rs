@@ -0,0 +1,165 @@
+use crate::constants::NUM_BASE_CODES;
+use crate::token::Token;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// The prefix of entries without one.
+const NO_PREFIX: u32 = u32::MAX;
+
+/// A dictionary entry, stored as a link to the entry without its last char.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LinkedEntry {
+    /// The code of the entry without its last char, or [`NO_PREFIX`] for single char entries.
+    prefix: u32,
+
+    /// The length of the entry.
+    pub len: u32,
+
+    /// The first char of the entry.
+    pub first: u16,
+
+    /// The last char of the entry.
+    pub last: u16,
+}
+
+impl LinkedEntry {
+    /// The code of the entry without its last char, if it has more than one char.
+    #[inline]
+    pub fn prefix(&self) -> Option<u32> {
+        if self.prefix == NO_PREFIX {
+            None
+        } else {
+            Some(self.prefix)
+        }
+    }
+}
+
+/// The decompressor's dictionary, where every entry only stores its last char.
+///
+/// Every entry besides the single char ones is an earlier entry plus one char,
+/// so this takes constant memory per entry instead of memory proportional to its length.
+#[derive(Debug, Clone)]
+pub(crate) struct LinkedDictionary {
+    entries: Vec<LinkedEntry>,
+
+    /// The code of the last entry that was produced, w.
+    w: Option<u32>,
+}
+
+impl LinkedDictionary {
+    /// Make a new [`LinkedDictionary`].
+    pub fn new() -> Self {
+        // The base codes are never looked up.
+        let base = LinkedEntry {
+            prefix: NO_PREFIX,
+            len: 1,
+            first: 0,
+            last: 0,
+        };
+
+        LinkedDictionary {
+            entries: vec![base; usize::from(NUM_BASE_CODES)],
+            w: None,
+        }
+    }
+
+    /// Remove every entry besides the base codes, keeping the allocation.
+    #[cfg(feature = "rayon")]
+    pub fn clear(&mut self) {
+        self.entries.truncate(usize::from(NUM_BASE_CODES));
+        self.w = None;
+    }
+
+    /// Get an entry.
+    #[inline]
+    pub fn get(&self, code: u32) -> Option<&LinkedEntry> {
+        self.entries.get(usize::try_from(code).ok()?)
+    }
+
+    /// The number of entries, including the base codes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    fn next_code(&self) -> u32 {
+        // The dictionary cannot grow past the largest code.
+        u32::try_from(self.entries.len()).unwrap()
+    }
+
+    /// Update the dictionary with a token, returning the code of the entry it produced.
+    ///
+    /// Returns `None` for [`Token::Close`] or a token that does not refer to a valid entry.
+    pub fn push(&mut self, token: Token) -> Option<u32> {
+        let (code, first) = match token {
+            Token::U8(c) => {
+                let c = u16::from(c);
+                let code = self.push_char(c);
+                (code, c)
+            }
+            Token::U16(c) => {
+                let code = self.push_char(c);
+                (code, c)
+            }
+            Token::Reference(code) => match self.get(code) {
+                Some(entry) => (code, entry.first),
+                // The entry that is about to be created is w+w[0].
+                None if code == self.next_code() => (code, self.get(self.w?)?.first),
+                None => return None,
+            },
+            Token::Close => return None,
+        };
+
+        // Add w+entry[0] to the dictionary.
+        if let Some(w) = self.w {
+            let prefix = self.entries[usize::try_from(w).unwrap()];
+            self.entries.push(LinkedEntry {
+                prefix: w,
+                len: prefix.len + 1,
+                first: prefix.first,
+                last: first,
+            });
+        }
+
+        self.w = Some(code);
+
+        Some(code)
+    }
+
+    fn push_char(&mut self, c: u16) -> u32 {
+        let code = self.next_code();
+        self.entries.push(LinkedEntry {
+            prefix: NO_PREFIX,
+            len: 1,
+            first: c,
+            last: c,
+        });
+        code
+    }
+
+    /// Append the chars of an entry in `start..end` to `output`.
+    ///
+    /// # Panics
+    /// Panics if the code or range is invalid.
+    pub fn extend_from_entry(&self, code: u32, start: u32, end: u32, output: &mut Vec<u16>) {
+        let mut entry = self.entries[usize::try_from(code).unwrap()];
+        assert!(start <= end && end <= entry.len);
+
+        let output_start = output.len();
+        while entry.len > start {
+            if entry.len <= end {
+                output.push(entry.last);
+            }
+
+            match entry.prefix() {
+                Some(prefix) => entry = self.entries[usize::try_from(prefix).unwrap()],
+                None => break,
+            }
+        }
+
+        // The chars were visited from the end.
+        output[output_start..].reverse();
+    }
+}
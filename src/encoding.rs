@@ -0,0 +1,156 @@
+#[cfg(feature = "alloc")]
+use crate::constants::BASE64_KEY;
+use crate::constants::BASE64_VALUES;
+use crate::constants::NO_VALUE;
+#[cfg(feature = "alloc")]
+use crate::constants::URI_KEY;
+use crate::constants::URI_VALUES;
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// The ways a compressed stream can be represented as a string.
+///
+/// Each variant corresponds to a pair of compression and decompression functions.
+/// Data from [`crate::compress_to_uint8_array`] is [`Encoding::Raw`],
+/// after combining each pair of bytes into a big endian `u16`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// The output of [`crate::compress`], which may be invalid UTF16.
+    Raw,
+
+    /// The output of [`crate::compress_to_utf16`].
+    Utf16,
+
+    /// The output of [`crate::compress_to_encoded_uri_component`].
+    EncodedUriComponent,
+
+    /// The output of [`crate::compress_to_base64`].
+    Base64,
+}
+
+impl Encoding {
+    /// The number of bits stored in each char.
+    #[inline]
+    pub fn bits_per_char(self) -> u8 {
+        match self {
+            Self::Raw => 16,
+            Self::Utf16 => 15,
+            Self::EncodedUriComponent | Self::Base64 => 6,
+        }
+    }
+
+    /// Map a value of the bit stream to a char of the output.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub(crate) fn value_to_char(self, n: u16) -> u16 {
+        match self {
+            Self::Raw => n,
+            Self::Utf16 => n + 32,
+            Self::EncodedUriComponent => u16::from(URI_KEY[usize::from(n)]),
+            Self::Base64 => u16::from(BASE64_KEY[usize::from(n)]),
+        }
+    }
+
+    /// Map a char of the input to a value of the bit stream.
+    ///
+    /// Returns `None` if the char is not part of the alphabet.
+    /// Like the reference implementation, such chars are skipped.
+    #[inline]
+    pub(crate) fn char_to_value(self, c: u16) -> Option<u16> {
+        match self {
+            Self::Raw => Some(c),
+            Self::Utf16 => Some(c.wrapping_sub(32)),
+            // Only ASCII chars are part of the alphabet.
+            Self::EncodedUriComponent | Self::Base64 => self.byte_to_value(u8::try_from(c).ok()?),
+        }
+    }
+
+    /// Map a byte of the input to a value of the bit stream,
+    /// for [`Encoding::EncodedUriComponent`] and [`Encoding::Base64`].
+    ///
+    /// Their alphabets are ASCII, so the bytes of UTF-8 input can be used without decoding it.
+    /// Returns `None` if the byte is not part of the alphabet.
+    ///
+    /// # Panics
+    /// Panics for other encodings.
+    #[inline]
+    pub(crate) fn byte_to_value(self, b: u8) -> Option<u16> {
+        let values = match self {
+            Self::EncodedUriComponent => &URI_VALUES,
+            Self::Base64 => &BASE64_VALUES,
+            Self::Raw | Self::Utf16 => panic!("{:?} does not have an ASCII alphabet", self),
+        };
+
+        match values[usize::from(b)] {
+            NO_VALUE => None,
+            value => Some(value.into()),
+        }
+    }
+
+    /// Map the chars of the input to the values of the bit stream.
+    #[inline]
+    pub(crate) fn values<I>(self, compressed: I) -> impl Iterator<Item = u16>
+    where
+        I: Iterator<Item = u16>,
+    {
+        compressed.filter_map(move |c| self.char_to_value(c))
+    }
+
+    /// The values the padding adds to the bit stream after `len` chars of compressed data.
+    ///
+    /// Padding chars are usually outside of the alphabet, but not always skipped.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn padding_values(self, len: usize) -> Vec<u16> {
+        let mut padded = vec![self.value_to_char(0); len];
+        self.pad(&mut padded);
+        self.values(padded.drain(len..)).collect()
+    }
+
+    /// The number of padding chars at the start of `chars`.
+    ///
+    /// This accepts any number of '=' for [`Encoding::Base64`],
+    /// as implementations disagree on how many to add.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub(crate) fn padding_len(self, chars: &[u16]) -> usize {
+        match self {
+            Self::Raw | Self::EncodedUriComponent => 0,
+            Self::Utf16 => usize::from(chars.first() == Some(&u16::from(b' '))),
+            Self::Base64 => chars.iter().take_while(|&&c| c == u16::from(b'=')).count(),
+        }
+    }
+
+    /// The length of compressed data after adding the padding.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub(crate) fn padded_len(self, len: usize) -> usize {
+        match self {
+            Self::Raw | Self::EncodedUriComponent => len,
+            Self::Utf16 => len + 1,
+            Self::Base64 => match len % 4 {
+                0 => len,
+                mod_4 => len + (4 + 1) - mod_4,
+            },
+        }
+    }
+
+    /// The char used for padding.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub(crate) fn padding_char(self) -> u16 {
+        match self {
+            Self::Utf16 => u16::from(b' '),
+            _ => u16::from(b'='),
+        }
+    }
+
+    /// Add the padding the reference implementation adds after the compressed data.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub(crate) fn pad(self, compressed: &mut Vec<u16>) {
+        compressed.resize(self.padded_len(compressed.len()), self.padding_char());
+    }
+}
@@ -0,0 +1,272 @@
+use crate::constants::NUM_BASE_CODES;
+use crate::encoding::Encoding;
+use crate::token::Token;
+use crate::token::TokenError;
+use crate::token::Tokenizer;
+use crate::IntoWideIter;
+use core::convert::TryFrom;
+
+/// An entry of the dictionary used by [`decompress_into`].
+///
+/// Entries do not hold any data.
+/// Each entry is the start and length of a run of the decompressed data,
+/// which is always a run that was already written to the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DictionaryEntry {
+    pub(crate) start: usize,
+    pub(crate) len: usize,
+}
+
+impl DictionaryEntry {
+    /// An empty entry, to fill dictionary storage with.
+    pub const EMPTY: Self = DictionaryEntry { start: 0, len: 0 };
+}
+
+/// An error from decompressing into fixed-capacity buffers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FixedError {
+    /// The compressed data could not be decompressed.
+    InvalidData(TokenError),
+
+    /// The decompressed data does not fit in the output buffer.
+    OutputFull,
+
+    /// The dictionary does not fit in the dictionary buffer.
+    DictionaryFull,
+}
+
+impl core::fmt::Display for FixedError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidData(error) => write!(f, "invalid compressed data: {error}"),
+            Self::OutputFull => write!(f, "the output buffer is full"),
+            Self::DictionaryFull => write!(f, "the dictionary buffer is full"),
+        }
+    }
+}
+
+impl core::error::Error for FixedError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidData(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// The part of a buffer that was written to.
+struct Output<'a> {
+    buffer: &'a mut [u16],
+    len: usize,
+}
+
+impl Output<'_> {
+    #[inline]
+    fn push(&mut self, c: u16) -> Result<(), FixedError> {
+        *self
+            .buffer
+            .get_mut(self.len)
+            .ok_or(FixedError::OutputFull)? = c;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Copy an entry to the end of the output.
+    #[inline]
+    fn push_entry(&mut self, entry: DictionaryEntry) -> Result<(), FixedError> {
+        let end = self
+            .len
+            .checked_add(entry.len)
+            .filter(|&end| end <= self.buffer.len())
+            .ok_or(FixedError::OutputFull)?;
+
+        self.buffer
+            .copy_within(entry.start..entry.start + entry.len, self.len);
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// The part of a buffer that holds dictionary entries, without the base codes.
+struct Dictionary<'a> {
+    entries: &'a mut [DictionaryEntry],
+    len: usize,
+}
+
+impl Dictionary<'_> {
+    #[inline]
+    fn push(&mut self, entry: DictionaryEntry) -> Result<(), FixedError> {
+        *self
+            .entries
+            .get_mut(self.len)
+            .ok_or(FixedError::DictionaryFull)? = entry;
+        self.len += 1;
+        Ok(())
+    }
+
+    #[inline]
+    fn get(&self, code: u32) -> Option<DictionaryEntry> {
+        let index = usize::try_from(code)
+            .ok()?
+            .checked_sub(usize::from(NUM_BASE_CODES))?;
+        self.entries[..self.len].get(index).copied()
+    }
+}
+
+/// Decompress data into buffers provided by the caller, without allocating.
+///
+/// Returns the number of `u16`s written to `output`.
+/// Dictionary entries refer to runs of the output instead of holding their own data,
+/// so `dictionary` only needs to hold the start and length of each entry.
+/// A dictionary with twice as many entries as the length of the decompressed data is always enough.
+///
+/// # Errors
+/// Returns an error if the compressed data could not be decompressed,
+/// or if the output or dictionary do not fit in their buffers.
+/// On error, the contents of the buffers are unspecified.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::{DictionaryEntry, Encoding};
+/// let compressed = lz_str::compress_to_base64("The quick brown fox");
+///
+/// let mut output = [0; 32];
+/// let mut dictionary = [DictionaryEntry::EMPTY; 64];
+/// let len = lz_str::decompress_into(&compressed, Encoding::Base64, &mut output, &mut dictionary)
+///     .expect("invalid data");
+/// assert_eq!(String::from_utf16(&output[..len]).unwrap(), "The quick brown fox");
+/// ```
+pub fn decompress_into(
+    compressed: impl IntoWideIter,
+    encoding: Encoding,
+    output: &mut [u16],
+    dictionary: &mut [DictionaryEntry],
+) -> Result<usize, FixedError> {
+    let compressed = encoding.values(compressed.into_wide_iter());
+    let mut output = Output {
+        buffer: output,
+        len: 0,
+    };
+    let mut dictionary = Dictionary {
+        entries: dictionary,
+        len: 0,
+    };
+
+    // The previous entry, which is always right before the current one in the output.
+    let mut w = DictionaryEntry::EMPTY;
+    for token in Tokenizer::new(compressed, encoding.bits_per_char()) {
+        let start = output.len;
+        let entry = match token.map_err(FixedError::InvalidData)? {
+            Token::U8(c) => {
+                let entry = DictionaryEntry { start, len: 1 };
+                dictionary.push(entry)?;
+                output.push(c.into())?;
+                entry
+            }
+            Token::U16(c) => {
+                let entry = DictionaryEntry { start, len: 1 };
+                dictionary.push(entry)?;
+                output.push(c)?;
+                entry
+            }
+            Token::Reference(code) => match dictionary.get(code) {
+                Some(entry) => {
+                    output.push_entry(entry)?;
+                    DictionaryEntry {
+                        start,
+                        len: entry.len,
+                    }
+                }
+                // The tokenizer validated the code,
+                // so a missing entry is the one that is about to be created.
+                None if w.len != 0 => {
+                    output.push_entry(w)?;
+                    let first = output.buffer[w.start];
+                    output.push(first)?;
+                    DictionaryEntry {
+                        start,
+                        len: w.len + 1,
+                    }
+                }
+                None => return Err(FixedError::InvalidData(TokenError::InvalidReference(code))),
+            },
+            Token::Close => break,
+        };
+
+        // Add w+entry[0] to the dictionary.
+        // The entry follows w in the output, so this is a run of the output as well.
+        if w.len != 0 {
+            dictionary.push(DictionaryEntry {
+                start: w.start,
+                len: w.len + 1,
+            })?;
+        }
+
+        w = entry;
+    }
+
+    Ok(output.len)
+}
+
+/// A decompressor with fixed-capacity buffers, for use without an allocator.
+///
+/// `OUTPUT` is the most `u16`s of decompressed data it can hold,
+/// and `DICTIONARY` is the most dictionary entries it can hold.
+/// See [`decompress_into`] for how large the dictionary needs to be.
+/// As this holds both buffers inline, it is best kept in a `static` or on a large enough stack.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::{Encoding, FixedDecompressor, FixedError};
+/// let compressed = lz_str::compress_to_utf16("The quick brown fox");
+///
+/// let mut decompressor = FixedDecompressor::<32, 64>::new();
+/// let decompressed = decompressor
+///     .decompress(&compressed, Encoding::Utf16)
+///     .expect("invalid data");
+/// assert_eq!(String::from_utf16(decompressed).unwrap(), "The quick brown fox");
+///
+/// let mut decompressor = FixedDecompressor::<8, 64>::new();
+/// assert_eq!(
+///     decompressor.decompress(&compressed, Encoding::Utf16),
+///     Err(FixedError::OutputFull)
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct FixedDecompressor<const OUTPUT: usize, const DICTIONARY: usize> {
+    output: [u16; OUTPUT],
+    dictionary: [DictionaryEntry; DICTIONARY],
+}
+
+impl<const OUTPUT: usize, const DICTIONARY: usize> FixedDecompressor<OUTPUT, DICTIONARY> {
+    /// Make a new [`FixedDecompressor`].
+    pub const fn new() -> Self {
+        FixedDecompressor {
+            output: [0; OUTPUT],
+            dictionary: [DictionaryEntry::EMPTY; DICTIONARY],
+        }
+    }
+
+    /// Decompress data, returning the decompressed data in the output buffer.
+    ///
+    /// # Errors
+    /// Returns an error if the compressed data could not be decompressed,
+    /// or if the output or dictionary do not fit in their buffers.
+    pub fn decompress(
+        &mut self,
+        compressed: impl IntoWideIter,
+        encoding: Encoding,
+    ) -> Result<&[u16], FixedError> {
+        let len = decompress_into(compressed, encoding, &mut self.output, &mut self.dictionary)?;
+
+        Ok(&self.output[..len])
+    }
+}
+
+impl<const OUTPUT: usize, const DICTIONARY: usize> Default
+    for FixedDecompressor<OUTPUT, DICTIONARY>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
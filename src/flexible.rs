@@ -0,0 +1,150 @@
+use crate::compress::HashMap;
+use crate::constants::NUM_BASE_CODES;
+use crate::token::Token;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// The parent of all single char entries.
+///
+/// This is never a valid code, as codes are limited by the dictionary size.
+const ROOT: u32 = u32::MAX;
+
+/// A model of the dictionary the decompressor builds.
+///
+/// Entries are stored as a trie,
+/// mapping a parent entry and the next char to the child entry.
+/// This works as every prefix of an entry is also an entry.
+#[derive(Debug)]
+struct Trie {
+    children: HashMap<(u32, u16), u32>,
+
+    /// The number of entries the decompressor has created, including the base codes.
+    ///
+    /// This may be larger than the number of nodes,
+    /// as the decompressor will happily create duplicate entries.
+    dictionary_len: u32,
+}
+
+impl Trie {
+    fn new() -> Self {
+        Trie {
+            children: HashMap::default(),
+            dictionary_len: NUM_BASE_CODES.into(),
+        }
+    }
+
+    /// Create the next entry.
+    ///
+    /// A duplicate entry still takes up a code, but the existing node is kept.
+    fn push(&mut self, parent: u32, c: u16) {
+        self.children
+            .entry((parent, c))
+            .or_insert(self.dictionary_len);
+        self.dictionary_len += 1;
+    }
+
+    /// Walk the longest entry that is a prefix of `data`,
+    /// calling `f` with the code of each prefix along the way.
+    fn walk(&self, data: &[u16], mut f: impl FnMut(u32)) {
+        let mut node = ROOT;
+        for &c in data {
+            match self.children.get(&(node, c)) {
+                Some(&child) => {
+                    node = child;
+                    f(node);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// The length of the longest entry that is a prefix of `data`.
+    fn longest_match(&self, data: &[u16]) -> usize {
+        let mut len = 0;
+        self.walk(data, |_| len += 1);
+        len
+    }
+}
+
+/// Split the input into codes using flexible parsing.
+///
+/// The greedy parser always takes the longest entry.
+/// Instead, this picks the prefix of the longest entry that makes the combined length
+/// of this entry and the next one as long as possible.
+/// Shorter entries also make for shorter dictionary entries later on,
+/// so a shorter entry is only taken if it covers at least `min_gain` more chars than the longest one.
+///
+/// The decompressor only ever adds the previous entry plus the first char of the current one,
+/// no matter how the input was split.
+/// By tracking that dictionary instead of the greedy compressor's,
+/// the output stays decodable by any lz-string decompressor.
+pub(crate) fn flexible_parse(data: &[u16], min_gain: usize) -> Vec<Token> {
+    let mut tokens = Vec::with_capacity(data.len() >> 1);
+    let mut trie = Trie::new();
+    let mut codes = Vec::new();
+
+    // The code of the previous entry.
+    let mut previous = None;
+    let mut i = 0;
+    while i < data.len() {
+        let c = data[i];
+
+        if !trie.children.contains_key(&(ROOT, c)) {
+            // A new char must be sent as-is, which also creates its entry.
+            let token = match u8::try_from(c) {
+                Ok(c) => Token::U8(c),
+                Err(_) => Token::U16(c),
+            };
+            tokens.push(token);
+
+            let code = trie.dictionary_len;
+            trie.push(ROOT, c);
+            if let Some(previous) = previous {
+                trie.push(previous, c);
+            }
+
+            previous = Some(code);
+            i += 1;
+            continue;
+        }
+
+        // The entry for the previous entry plus this char exists as soon as this code is read,
+        // so it may be used immediately.
+        if let Some(previous) = previous {
+            trie.push(previous, c);
+        }
+
+        codes.clear();
+        trie.walk(&data[i..], |code| codes.push(code));
+
+        let mut best_len = codes.len();
+        let mut best_score = 0;
+        for len in (1..=codes.len()).rev() {
+            // The longest entry always wins ties.
+            let next = &data[i + len..];
+            let next_len = if next.is_empty() {
+                0
+            } else {
+                // A new char is still a single code.
+                trie.longest_match(next).max(1)
+            };
+
+            let mut score = len + next_len;
+            if len == codes.len() {
+                score += min_gain - 1;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_len = len;
+            }
+        }
+
+        let code = codes[best_len - 1];
+        tokens.push(Token::Reference(code));
+        previous = Some(code);
+        i += best_len;
+    }
+
+    tokens
+}
@@ -0,0 +1,306 @@
+use crate::compress::compress_with_encoding;
+use crate::crc32::Crc32;
+use crate::encoding::Encoding;
+use crate::prefix::decompress_whole;
+use crate::token::TokenError;
+use crate::IntoWideIter;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::convert::TryInto;
+
+/// The first bytes of a frame.
+const MAGIC: [u8; 4] = *b"LZSF";
+
+/// The version of the frame format this library writes.
+const VERSION: u8 = 1;
+
+/// The number of bytes in a frame header.
+const HEADER_LEN: usize = 4 + 1 + 1 + 8 + 4;
+
+/// An error from reading a frame or a chunked container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameError {
+    /// The frame is shorter than its header.
+    TooShort,
+
+    /// The frame does not start with the magic bytes.
+    InvalidMagic,
+
+    /// The frame has a version this library does not know.
+    UnsupportedVersion(u8),
+
+    /// The frame has an encoding this library does not know.
+    UnknownEncoding(u8),
+
+    /// The payload is not valid for its encoding,
+    /// like invalid UTF-8 or an odd number of bytes for [`Encoding::Raw`],
+    /// or has data after the end of its stream besides the encoding's padding.
+    InvalidPayload,
+
+    /// The payload could not be decompressed.
+    InvalidData(TokenError),
+
+    /// The length of the decompressed data does not match the header.
+    LengthMismatch {
+        /// The length in the header.
+        expected: u64,
+
+        /// The length of the decompressed data.
+        actual: u64,
+    },
+
+    /// The checksum of the decompressed data does not match the header.
+    ChecksumMismatch {
+        /// The checksum in the header.
+        expected: u32,
+
+        /// The checksum of the decompressed data.
+        actual: u32,
+    },
+
+    /// The requested range is out of bounds.
+    OutOfBounds,
+}
+
+impl core::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooShort => write!(f, "the frame is too short"),
+            Self::InvalidMagic => write!(f, "the frame has invalid magic bytes"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "the frame version {version} is not supported")
+            }
+            Self::UnknownEncoding(id) => write!(f, "the frame encoding {id} is unknown"),
+            Self::InvalidPayload => write!(f, "the payload is not valid for its encoding"),
+            Self::InvalidData(error) => write!(f, "invalid compressed data: {error}"),
+            Self::LengthMismatch { expected, actual } => write!(
+                f,
+                "expected {expected} decompressed chars, but got {actual}"
+            ),
+            Self::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "expected a checksum of {expected:08x}, but got {actual:08x}"
+            ),
+            Self::OutOfBounds => write!(f, "the range is out of bounds"),
+        }
+    }
+}
+
+impl core::error::Error for FrameError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidData(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Get the id of an encoding in a header.
+pub(crate) fn encoding_id(encoding: Encoding) -> u8 {
+    match encoding {
+        Encoding::Raw => 0,
+        Encoding::Utf16 => 1,
+        Encoding::EncodedUriComponent => 2,
+        Encoding::Base64 => 3,
+    }
+}
+
+/// Get the encoding with an id from a header.
+pub(crate) fn encoding_from_id(id: u8) -> Result<Encoding, FrameError> {
+    match id {
+        0 => Ok(Encoding::Raw),
+        1 => Ok(Encoding::Utf16),
+        2 => Ok(Encoding::EncodedUriComponent),
+        3 => Ok(Encoding::Base64),
+        id => Err(FrameError::UnknownEncoding(id)),
+    }
+}
+
+/// Write the chars of a compressed stream as bytes.
+///
+/// The chars are stored as UTF-8, except for [`Encoding::Raw`],
+/// which is stored like the output of [`crate::compress_to_uint8_array`].
+pub(crate) fn write_payload(compressed: &[u16], encoding: Encoding, output: &mut Vec<u8>) {
+    match encoding {
+        Encoding::Raw => {
+            for c in compressed {
+                output.extend(c.to_be_bytes());
+            }
+        }
+        // The other encodings only produce valid UTF-16.
+        _ => output.extend(String::from_utf16(compressed).unwrap().into_bytes()),
+    }
+}
+
+/// Decompress a payload written by [`write_payload`].
+///
+/// Anything after the stream besides the encoding's padding is rejected.
+pub(crate) fn decompress_payload(
+    payload: &[u8],
+    encoding: Encoding,
+) -> Result<Vec<u16>, FrameError> {
+    let compressed: Vec<u16> = match encoding {
+        Encoding::Raw => {
+            if payload.len() & 1 == 1 {
+                return Err(FrameError::InvalidPayload);
+            }
+
+            payload
+                .chunks(2)
+                // The chunks always have 2 bytes.
+                .map(|bytes| u16::from_be_bytes(bytes.try_into().unwrap()))
+                .collect()
+        }
+        _ => core::str::from_utf8(payload)
+            .map_err(|_| FrameError::InvalidPayload)?
+            .encode_utf16()
+            .collect(),
+    };
+
+    match decompress_whole(&compressed, encoding).map_err(FrameError::InvalidData)? {
+        (decompressed, false) => Ok(decompressed),
+        (_, true) => Err(FrameError::InvalidPayload),
+    }
+}
+
+/// The header of a frame.
+///
+/// A frame is laid out as:
+///
+/// | Bytes | Field |
+/// |-------|-------|
+/// | 4     | The magic bytes, "LZSF" |
+/// | 1     | The version, currently 1 |
+/// | 1     | The [`Encoding`] of the payload |
+/// | 8     | The length of the decompressed data in `u16`s, little endian |
+/// | 4     | The CRC-32 of the decompressed data, little endian |
+/// | ...   | The payload |
+///
+/// The payload is the output of the compress function for the encoding.
+/// It is stored as UTF-8, except for [`Encoding::Raw`],
+/// which is stored like the output of [`crate::compress_to_uint8_array`].
+/// The checksum is over the decompressed data as little endian bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrameHeader {
+    /// The version of the frame format.
+    pub version: u8,
+
+    /// The encoding of the payload.
+    pub encoding: Encoding,
+
+    /// The length of the decompressed data in `u16`s.
+    pub len: u64,
+
+    /// The CRC-32 of the decompressed data, as little endian bytes.
+    pub crc32: u32,
+}
+
+impl FrameHeader {
+    /// Read the header of a frame, returning it along with the payload.
+    ///
+    /// The payload can be decompressed by any lz-string implementation.
+    ///
+    /// # Errors
+    /// Returns an error if the header is invalid.
+    pub fn read(frame: &[u8]) -> Result<(Self, &[u8]), FrameError> {
+        if frame.len() < HEADER_LEN {
+            return Err(FrameError::TooShort);
+        }
+        let (header, payload) = frame.split_at(HEADER_LEN);
+
+        if header[..4] != MAGIC {
+            return Err(FrameError::InvalidMagic);
+        }
+
+        let version = header[4];
+        if version != VERSION {
+            return Err(FrameError::UnsupportedVersion(version));
+        }
+
+        let encoding = encoding_from_id(header[5])?;
+
+        // The slices have the right lengths.
+        let len = u64::from_le_bytes(header[6..14].try_into().unwrap());
+        let crc32 = u32::from_le_bytes(header[14..18].try_into().unwrap());
+
+        Ok((
+            FrameHeader {
+                version,
+                encoding,
+                len,
+                crc32,
+            },
+            payload,
+        ))
+    }
+}
+
+/// Compress data into a frame, with a header describing it.
+///
+/// See [`FrameHeader`] for the layout.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::{Encoding, FrameHeader};
+/// let frame = lz_str::encode_framed("The quick brown fox", Encoding::Base64);
+///
+/// let decompressed = lz_str::decode_framed(&frame).expect("invalid frame");
+/// assert_eq!(String::from_utf16(&decompressed).unwrap(), "The quick brown fox");
+///
+/// // The payload is a plain lz-string stream.
+/// let (_, payload) = FrameHeader::read(&frame).expect("invalid header");
+/// let payload = std::str::from_utf8(payload).unwrap();
+/// assert_eq!(lz_str::decompress_from_base64(payload), Some(decompressed));
+/// ```
+pub fn encode_framed(data: impl IntoWideIter, encoding: Encoding) -> Vec<u8> {
+    let data: Vec<u16> = data.into_wide_iter().collect();
+    let compressed = compress_with_encoding(&data, encoding);
+
+    let mut crc32 = Crc32::new();
+    crc32.update_wide(&data);
+
+    let mut frame = Vec::with_capacity(HEADER_LEN + compressed.len() * 2);
+    frame.extend(MAGIC);
+    frame.push(VERSION);
+    frame.push(encoding_id(encoding));
+    // usize is at most 64 bits.
+    frame.extend(u64::try_from(data.len()).unwrap().to_le_bytes());
+    frame.extend(crc32.finish().to_le_bytes());
+
+    write_payload(&compressed, encoding, &mut frame);
+
+    frame
+}
+
+/// Decompress a frame, verifying it against its header.
+///
+/// # Errors
+/// Returns an error if the frame is invalid,
+/// or if the decompressed data does not match its header.
+pub fn decode_framed(frame: &[u8]) -> Result<Vec<u16>, FrameError> {
+    let (header, payload) = FrameHeader::read(frame)?;
+
+    let decompressed = decompress_payload(payload, header.encoding)?;
+
+    // usize is at most 64 bits.
+    let len = u64::try_from(decompressed.len()).unwrap();
+    if len != header.len {
+        return Err(FrameError::LengthMismatch {
+            expected: header.len,
+            actual: len,
+        });
+    }
+
+    let mut crc32 = Crc32::new();
+    crc32.update_wide(&decompressed);
+    let crc32 = crc32.finish();
+    if crc32 != header.crc32 {
+        return Err(FrameError::ChecksumMismatch {
+            expected: header.crc32,
+            actual: crc32,
+        });
+    }
+
+    Ok(decompressed)
+}
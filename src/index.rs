@@ -0,0 +1,120 @@
+use crate::dictionary::LinkedDictionary;
+use crate::encoding::Encoding;
+use crate::token::Token;
+use crate::token::Tokenizer;
+use crate::IntoWideIter;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::ops::Range;
+
+/// An index over a compressed stream, allowing parts of it to be decompressed on demand.
+///
+/// Building the index reads the whole stream once,
+/// storing where each code's output starts along with the decompressor's dictionary.
+/// Dictionary entries only store a link to a shorter entry and one char,
+/// so the index is usually much smaller than the decompressed data.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::{Encoding, RandomAccessIndex};
+/// let compressed = lz_str::compress_to_base64("The quick brown fox jumps over the lazy dog");
+/// let index = RandomAccessIndex::new(&compressed, Encoding::Base64).expect("invalid data");
+///
+/// let fox: Vec<u16> = "fox".encode_utf16().collect();
+/// assert_eq!(index.extract(16..19), Some(fox));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RandomAccessIndex {
+    dictionary: LinkedDictionary,
+
+    /// The start of each code's output and the code itself, in stream order.
+    codes: Vec<(usize, u32)>,
+
+    /// The length of the decompressed data.
+    len: usize,
+}
+
+impl RandomAccessIndex {
+    /// Build an index over the given compressed data.
+    ///
+    /// # Errors
+    /// Returns `None` if the compressed data could not be decompressed.
+    pub fn new(compressed: impl IntoWideIter, encoding: Encoding) -> Option<Self> {
+        let compressed = encoding.values(compressed.into_wide_iter());
+
+        let mut dictionary = LinkedDictionary::new();
+        let mut codes = Vec::new();
+        let mut len: usize = 0;
+        for token in Tokenizer::new(compressed, encoding.bits_per_char()) {
+            let token = token.ok()?;
+            if token == Token::Close {
+                break;
+            }
+
+            let code = dictionary.push(token)?;
+            codes.push((len, code));
+
+            let entry_len = dictionary.get(code)?.len;
+            len = len.checked_add(usize::try_from(entry_len).ok()?)?;
+        }
+
+        Some(RandomAccessIndex {
+            dictionary,
+            codes,
+            len,
+        })
+    }
+
+    /// The length of the decompressed data.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the decompressed data is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Decompress part of the data.
+    ///
+    /// Only the codes that overlap the range are decompressed.
+    ///
+    /// # Errors
+    /// Returns `None` if the range is out of bounds.
+    pub fn extract(&self, range: Range<usize>) -> Option<Vec<u16>> {
+        if range.start > range.end || range.end > self.len {
+            return None;
+        }
+
+        let mut output = Vec::with_capacity(range.len());
+        if range.is_empty() {
+            return Some(output);
+        }
+
+        // The last code that starts at or before the range.
+        let first = self
+            .codes
+            .partition_point(|(start, _)| *start <= range.start)
+            - 1;
+
+        for &(start, code) in self.codes[first..]
+            .iter()
+            .take_while(|(start, _)| *start < range.end)
+        {
+            let entry_len = self.dictionary.get(code)?.len;
+
+            // Both are within the entry, so they fit in a u32.
+            let entry_start = u32::try_from(range.start.saturating_sub(start)).unwrap();
+            let entry_end = u32::try_from(range.end - start)
+                .unwrap_or(entry_len)
+                .min(entry_len);
+
+            self.dictionary
+                .extend_from_entry(code, entry_start, entry_end, &mut output);
+        }
+
+        Some(output)
+    }
+}
@@ -0,0 +1,390 @@
+use crate::encoding::Encoding;
+use crate::frame::write_payload;
+use crate::stream::Compressor;
+use crate::stream::Decompressor;
+use std::convert::TryInto;
+use std::io;
+use std::task::ready;
+use std::task::Poll;
+
+/// The number of bytes read from the inner reader at a time.
+const BUFFER_LEN: usize = 8 * 1024;
+
+/// A transformation of a byte stream that takes its input in pieces.
+pub(crate) trait Transform {
+    /// Transform more input, appending the output to `output`.
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()>;
+
+    /// Finish the stream, appending the rest of the output to `output`.
+    fn finish(&mut self, output: &mut Vec<u8>) -> io::Result<()>;
+}
+
+/// Make an error for invalid data.
+fn invalid_data<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// A decoder for UTF-8 that takes its input in pieces.
+#[derive(Debug, Default)]
+struct Utf8Decoder {
+    /// The start of a char that was split between pieces.
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    /// Decode more input, appending it to `output` as UTF-16.
+    fn decode(&mut self, mut input: &[u8], output: &mut Vec<u16>) -> io::Result<()> {
+        // Finish the char that was split first.
+        while !self.pending.is_empty() && !input.is_empty() {
+            self.pending.push(input[0]);
+            input = &input[1..];
+
+            match std::str::from_utf8(&self.pending) {
+                Ok(s) => {
+                    output.extend(s.encode_utf16());
+                    self.pending.clear();
+                }
+                Err(error) if error.error_len().is_none() => {}
+                Err(error) => return Err(invalid_data(error)),
+            }
+        }
+
+        match std::str::from_utf8(input) {
+            Ok(s) => output.extend(s.encode_utf16()),
+            Err(error) if error.error_len().is_none() => {
+                let (valid, rest) = input.split_at(error.valid_up_to());
+                // The bytes were just validated.
+                output.extend(std::str::from_utf8(valid).unwrap().encode_utf16());
+                self.pending.extend(rest);
+            }
+            Err(error) => return Err(invalid_data(error)),
+        }
+
+        Ok(())
+    }
+
+    /// Check that no char was left incomplete.
+    fn finish(&self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            return Err(invalid_data("the data ends with an incomplete UTF-8 char"));
+        }
+
+        Ok(())
+    }
+}
+
+/// An encoder for UTF-8 that takes its input as UTF-16 in pieces.
+#[derive(Debug, Default)]
+struct Utf8Encoder {
+    /// The high surrogate of a pair that was split between pieces.
+    pending: Option<u16>,
+}
+
+impl Utf8Encoder {
+    /// Encode more input, appending it to `output`.
+    fn encode(&mut self, input: &[u16], output: &mut Vec<u8>) -> io::Result<()> {
+        let (input, last) = match input.split_last() {
+            Some((&last, rest)) if (0xD800..0xDC00).contains(&last) => (rest, Some(last)),
+            Some(_) => (input, None),
+            None => return Ok(()),
+        };
+        let pending = std::mem::replace(&mut self.pending, last);
+
+        let mut buffer = [0; 4];
+        for c in char::decode_utf16(pending.into_iter().chain(input.iter().copied())) {
+            let c = c.map_err(invalid_data)?;
+            output.extend(c.encode_utf8(&mut buffer).as_bytes());
+        }
+
+        Ok(())
+    }
+
+    /// Check that no surrogate pair was left incomplete.
+    fn finish(&self) -> io::Result<()> {
+        if self.pending.is_some() {
+            return Err(invalid_data("the data ends with an unpaired surrogate"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Compress UTF-8 text into the bytes of an encoding.
+///
+/// The compressed chars are stored like the payload of [`crate::encode_framed`].
+#[derive(Debug)]
+pub(crate) struct ByteCompressor {
+    encoding: Encoding,
+    compressor: Option<Compressor>,
+    decoder: Utf8Decoder,
+
+    /// Buffers reused between pieces.
+    data: Vec<u16>,
+    compressed: Vec<u16>,
+}
+
+impl ByteCompressor {
+    pub fn new(encoding: Encoding) -> Self {
+        ByteCompressor {
+            encoding,
+            compressor: Some(Compressor::new(encoding)),
+            decoder: Utf8Decoder::default(),
+
+            data: Vec::new(),
+            compressed: Vec::new(),
+        }
+    }
+}
+
+impl Transform for ByteCompressor {
+    fn transform(&mut self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+        let compressor = self
+            .compressor
+            .as_mut()
+            .ok_or_else(|| io::Error::other("the stream was already finished"))?;
+
+        self.data.clear();
+        self.decoder.decode(input, &mut self.data)?;
+
+        self.compressed.clear();
+        compressor.write(&self.data, &mut self.compressed);
+        write_payload(&self.compressed, self.encoding, output);
+
+        Ok(())
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) -> io::Result<()> {
+        let compressor = self
+            .compressor
+            .take()
+            .ok_or_else(|| io::Error::other("the stream was already finished"))?;
+        self.decoder.finish()?;
+
+        self.compressed.clear();
+        compressor.finish(&mut self.compressed);
+        write_payload(&self.compressed, self.encoding, output);
+
+        Ok(())
+    }
+}
+
+/// Decompress the bytes of an encoding into UTF-8 text.
+#[derive(Debug)]
+pub(crate) struct ByteDecompressor {
+    encoding: Encoding,
+    decompressor: Option<Decompressor>,
+    decoder: Utf8Decoder,
+    encoder: Utf8Encoder,
+
+    /// The first byte of a char of [`Encoding::Raw`] that was split between pieces.
+    pending: Option<u8>,
+
+    /// Buffers reused between pieces.
+    compressed: Vec<u16>,
+    data: Vec<u16>,
+}
+
+impl ByteDecompressor {
+    pub fn new(encoding: Encoding) -> Self {
+        ByteDecompressor {
+            encoding,
+            decompressor: Some(Decompressor::new(encoding)),
+            decoder: Utf8Decoder::default(),
+            encoder: Utf8Encoder::default(),
+
+            pending: None,
+
+            compressed: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+}
+
+impl Transform for ByteDecompressor {
+    fn transform(&mut self, mut input: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+        let decompressor = self
+            .decompressor
+            .as_mut()
+            .ok_or_else(|| io::Error::other("the stream was already finished"))?;
+
+        self.compressed.clear();
+        match self.encoding {
+            Encoding::Raw => {
+                if let (Some(first), Some((&second, rest))) = (self.pending, input.split_first()) {
+                    self.compressed.push(u16::from_be_bytes([first, second]));
+                    self.pending = None;
+                    input = rest;
+                }
+
+                let chunks = input.chunks_exact(2);
+                if let [last] = chunks.remainder() {
+                    self.pending = Some(*last);
+                }
+                // The chunks always have 2 bytes.
+                self.compressed
+                    .extend(chunks.map(|bytes| u16::from_be_bytes(bytes.try_into().unwrap())));
+            }
+            _ => self.decoder.decode(input, &mut self.compressed)?,
+        }
+
+        self.data.clear();
+        decompressor
+            .write(&self.compressed, &mut self.data)
+            .map_err(invalid_data)?;
+        self.encoder.encode(&self.data, output)
+    }
+
+    fn finish(&mut self, _output: &mut Vec<u8>) -> io::Result<()> {
+        let decompressor = self
+            .decompressor
+            .take()
+            .ok_or_else(|| io::Error::other("the stream was already finished"))?;
+
+        if self.pending.is_some() {
+            return Err(invalid_data("the data ends with an incomplete char"));
+        }
+        self.decoder.finish()?;
+        decompressor.finish().map_err(invalid_data)?;
+        self.encoder.finish()
+    }
+}
+
+/// The state of a reader that transforms the data of another reader.
+#[derive(Debug)]
+pub(crate) struct ReadState<T> {
+    transform: T,
+
+    /// The buffer for reading from the inner reader.
+    input: Box<[u8]>,
+
+    /// Output that was not read yet, starting at `position`.
+    output: Vec<u8>,
+    position: usize,
+
+    /// Whether the inner reader reached its end and the transform was finished.
+    done: bool,
+}
+
+impl<T> ReadState<T>
+where
+    T: Transform,
+{
+    pub fn new(transform: T) -> Self {
+        ReadState {
+            transform,
+            input: vec![0; BUFFER_LEN].into_boxed_slice(),
+            output: Vec::new(),
+            position: 0,
+            done: false,
+        }
+    }
+
+    /// Read transformed data into `buf`, reading from the inner reader with `read` when more is needed.
+    pub fn poll_read<F>(&mut self, buf: &mut [u8], mut read: F) -> Poll<io::Result<usize>>
+    where
+        F: FnMut(&mut [u8]) -> Poll<io::Result<usize>>,
+    {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        loop {
+            let pending = &self.output[self.position..];
+            if !pending.is_empty() {
+                let len = pending.len().min(buf.len());
+                buf[..len].copy_from_slice(&pending[..len]);
+                self.position += len;
+                return Poll::Ready(Ok(len));
+            }
+
+            if self.done {
+                return Poll::Ready(Ok(0));
+            }
+
+            self.output.clear();
+            self.position = 0;
+            let len = ready!(read(&mut self.input))?;
+            if len == 0 {
+                self.done = true;
+                self.transform.finish(&mut self.output)?;
+            } else {
+                self.transform
+                    .transform(&self.input[..len], &mut self.output)?;
+            }
+        }
+    }
+}
+
+/// The state of a writer that transforms data before writing it to another writer.
+#[derive(Debug)]
+pub(crate) struct WriteState<T> {
+    transform: T,
+
+    /// Output that was not written yet, starting at `position`.
+    output: Vec<u8>,
+    position: usize,
+
+    /// Whether the transform was finished.
+    finished: bool,
+}
+
+impl<T> WriteState<T>
+where
+    T: Transform,
+{
+    pub fn new(transform: T) -> Self {
+        WriteState {
+            transform,
+            output: Vec::new(),
+            position: 0,
+            finished: false,
+        }
+    }
+
+    /// Write all of the pending output with `write`.
+    pub fn poll_write_output<F>(&mut self, mut write: F) -> Poll<io::Result<()>>
+    where
+        F: FnMut(&[u8]) -> Poll<io::Result<usize>>,
+    {
+        while self.position < self.output.len() {
+            let len = ready!(write(&self.output[self.position..]))?;
+            if len == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.position += len;
+        }
+
+        self.output.clear();
+        self.position = 0;
+        Poll::Ready(Ok(()))
+    }
+
+    /// Transform `buf`, after writing the output of earlier calls with `write`.
+    pub fn poll_write<F>(&mut self, buf: &[u8], write: F) -> Poll<io::Result<usize>>
+    where
+        F: FnMut(&[u8]) -> Poll<io::Result<usize>>,
+    {
+        ready!(self.poll_write_output(write))?;
+
+        self.transform.transform(buf, &mut self.output)?;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    /// Finish the transform and write the rest of the output with `write`.
+    pub fn poll_finish<F>(&mut self, mut write: F) -> Poll<io::Result<()>>
+    where
+        F: FnMut(&[u8]) -> Poll<io::Result<usize>>,
+    {
+        ready!(self.poll_write_output(&mut write))?;
+
+        if !self.finished {
+            self.finished = true;
+            self.transform.finish(&mut self.output)?;
+        }
+
+        self.poll_write_output(write)
+    }
+}
@@ -1,4 +1,11 @@
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "std"), no_std)]
+// Only the `avx2` module of the `simd` feature uses `unsafe`.
+// When it is built, `unsafe` is forbidden in every other module instead of the whole crate.
+#![cfg_attr(
+    not(all(feature = "simd", target_arch = "x86_64")),
+    forbid(unsafe_code)
+)]
+#![cfg_attr(all(feature = "simd", target_arch = "x86_64"), deny(unsafe_code))]
 #![deny(missing_docs)]
 #![warn(clippy::cast_lossless)]
 #![warn(clippy::cast_possible_wrap)]
@@ -39,23 +46,239 @@
 //! The [`IntoWideIter`] trait exists to ease the passing of data into functions.
 //! Most functions accept this generic parameter instead of a concrete type.
 //! Look at this trait's documentation to see what types this trait is implemented for.
+//!
+//! # `no_std`
+//! This library is `no_std` compatible with the `std` feature disabled.
+//! Most functions still need an allocator, which is enabled with the `hashbrown` feature.
+//! Without it, only [`decompress_into`] and [`FixedDecompressor`] are available,
+//! which decompress into buffers provided by the caller.
+//!
+//! Compared to the default `std` feature:
+//! - `compress_chunked` and `ChunkedReader` do not use threads.
+//! - Only inputs of up to 64 chars, instead of 256, are compressed on the stack,
+//!   as stacks may be small.
+//! - The maps of the compressor use the default hasher of hashbrown unless `rustc-hash` is enabled,
+//!   and `DictionaryHasher::SipHash` is not available.
+//! - The `tokio`, `futures-io`, `tokio-util`, `rayon`, `simd`, and `ahash` features are not available,
+//!   as they all enable `std`.
+
+#[cfg(all(feature = "alloc", not(feature = "std"), not(feature = "hashbrown")))]
+compile_error!("the `alloc` feature needs either the `std` or the `hashbrown` feature");
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod alphabet;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod append;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[allow(unsafe_code, clippy::cast_possible_wrap)]
+mod avx2;
+#[cfg(feature = "rayon")]
+#[forbid(unsafe_code)]
+mod batch;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod chunked;
+#[cfg(feature = "tokio-util")]
+#[forbid(unsafe_code)]
+mod codec;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
 mod compress;
+#[forbid(unsafe_code)]
 mod constants;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod crc32;
+#[forbid(unsafe_code)]
 mod decompress;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod dictionary;
+#[forbid(unsafe_code)]
+mod encoding;
+#[forbid(unsafe_code)]
+mod fixed;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod flexible;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod frame;
+#[cfg(feature = "futures-io")]
+#[forbid(unsafe_code)]
+pub mod futures;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod index;
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+#[forbid(unsafe_code)]
+mod io;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod partial;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod prefix;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod repair;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod search;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod small;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod split;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod stream;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod table;
+#[forbid(unsafe_code)]
+mod token;
+#[cfg(feature = "tokio")]
+#[forbid(unsafe_code)]
+pub mod tokio;
+#[cfg(feature = "alloc")]
+#[forbid(unsafe_code)]
+mod trailer;
 
+#[cfg(feature = "alloc")]
+pub use crate::append::append;
+#[cfg(feature = "rayon")]
+pub use crate::batch::compress_batch;
+#[cfg(feature = "rayon")]
+pub use crate::batch::decompress_batch;
+#[cfg(feature = "alloc")]
+pub use crate::chunked::compress_chunked;
+#[cfg(feature = "alloc")]
+pub use crate::chunked::ChunkedReader;
+#[cfg(feature = "tokio-util")]
+pub use crate::codec::CodecError;
+#[cfg(feature = "tokio-util")]
+pub use crate::codec::Framing;
+#[cfg(feature = "tokio-util")]
+pub use crate::codec::LzStringCodec;
+#[cfg(feature = "alloc")]
 pub use crate::compress::compress;
+#[cfg(feature = "alloc")]
+pub use crate::compress::compress_bounded;
+#[cfg(feature = "alloc")]
 pub use crate::compress::compress_internal;
+#[cfg(feature = "alloc")]
+pub use crate::compress::compress_internal_with_hasher;
+#[cfg(feature = "alloc")]
+pub use crate::compress::compress_internal_with_level;
+#[cfg(feature = "alloc")]
 pub use crate::compress::compress_to_base64;
+#[cfg(feature = "alloc")]
 pub use crate::compress::compress_to_encoded_uri_component;
+#[cfg(feature = "alloc")]
 pub use crate::compress::compress_to_uint8_array;
+#[cfg(feature = "alloc")]
 pub use crate::compress::compress_to_utf16;
+#[cfg(feature = "alloc")]
+pub use crate::compress::compress_with_hasher;
+#[cfg(feature = "alloc")]
+pub use crate::compress::Boundary;
+#[cfg(feature = "alloc")]
+pub use crate::compress::CompressionLevel;
+#[cfg(feature = "alloc")]
+pub use crate::compress::DictionaryHasher;
+#[cfg(feature = "alloc")]
 pub use crate::decompress::decompress;
+#[cfg(feature = "alloc")]
 pub use crate::decompress::decompress_from_base64;
+#[cfg(feature = "alloc")]
 pub use crate::decompress::decompress_from_encoded_uri_component;
+#[cfg(feature = "alloc")]
 pub use crate::decompress::decompress_from_uint8_array;
+#[cfg(feature = "alloc")]
 pub use crate::decompress::decompress_from_utf16;
+#[cfg(feature = "alloc")]
 pub use crate::decompress::decompress_internal;
+#[cfg(feature = "alloc")]
+pub use crate::decompress::decompressed_len;
+pub use crate::encoding::Encoding;
+pub use crate::fixed::decompress_into;
+pub use crate::fixed::DictionaryEntry;
+pub use crate::fixed::FixedDecompressor;
+pub use crate::fixed::FixedError;
+#[cfg(feature = "alloc")]
+pub use crate::frame::decode_framed;
+#[cfg(feature = "alloc")]
+pub use crate::frame::encode_framed;
+#[cfg(feature = "alloc")]
+pub use crate::frame::FrameError;
+#[cfg(feature = "alloc")]
+pub use crate::frame::FrameHeader;
+#[cfg(feature = "alloc")]
+pub use crate::index::RandomAccessIndex;
+#[cfg(feature = "alloc")]
+pub use crate::partial::decompress_partial;
+#[cfg(feature = "alloc")]
+pub use crate::partial::PartialDecompress;
+#[cfg(feature = "alloc")]
+pub use crate::prefix::decompress_prefix;
+#[cfg(feature = "alloc")]
+pub use crate::prefix::decompress_streams;
+#[cfg(feature = "alloc")]
+pub use crate::prefix::decompress_strict;
+#[cfg(feature = "alloc")]
+pub use crate::prefix::Streams;
+#[cfg(feature = "alloc")]
+pub use crate::repair::repair;
+#[cfg(feature = "alloc")]
+pub use crate::repair::repair_with_validator;
+#[cfg(feature = "alloc")]
+pub use crate::repair::Edit;
+#[cfg(feature = "alloc")]
+pub use crate::repair::Repair;
+#[cfg(feature = "alloc")]
+pub use crate::search::compressed_contains;
+#[cfg(feature = "alloc")]
+pub use crate::search::compressed_find_all;
+#[cfg(feature = "alloc")]
+pub use crate::split::split_payload;
+#[cfg(feature = "alloc")]
+pub use crate::split::ReassembleError;
+#[cfg(feature = "alloc")]
+pub use crate::split::Reassembler;
+#[cfg(feature = "alloc")]
+pub use crate::stream::Compressor;
+#[cfg(feature = "alloc")]
+pub use crate::stream::Decompressor;
+#[cfg(feature = "alloc")]
+pub use crate::table::StringTable;
+#[cfg(feature = "alloc")]
+pub use crate::table::StringTableBuilder;
+#[cfg(feature = "alloc")]
+pub use crate::token::encode_tokens;
+pub use crate::token::tokenize;
+pub use crate::token::Token;
+pub use crate::token::TokenError;
+pub use crate::token::Tokenizer;
+#[cfg(feature = "alloc")]
+pub use crate::trailer::compress_with_trailer;
+#[cfg(feature = "alloc")]
+pub use crate::trailer::decompress_with_trailer;
+#[cfg(feature = "alloc")]
+pub use crate::trailer::Trailer;
+#[cfg(feature = "alloc")]
+pub use crate::trailer::TrailerError;
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 
 /// A trait to make it easier to pass arguments to functions.
 pub trait IntoWideIter {
@@ -67,7 +290,7 @@ pub trait IntoWideIter {
 }
 
 impl<'a> IntoWideIter for &'a str {
-    type Iter = std::str::EncodeUtf16<'a>;
+    type Iter = core::str::EncodeUtf16<'a>;
 
     #[inline]
     fn into_wide_iter(self) -> Self::Iter {
@@ -76,7 +299,7 @@ impl<'a> IntoWideIter for &'a str {
 }
 
 impl<'a> IntoWideIter for &&'a str {
-    type Iter = std::str::EncodeUtf16<'a>;
+    type Iter = core::str::EncodeUtf16<'a>;
 
     #[inline]
     fn into_wide_iter(self) -> Self::Iter {
@@ -84,8 +307,9 @@ impl<'a> IntoWideIter for &&'a str {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a> IntoWideIter for &'a String {
-    type Iter = std::str::EncodeUtf16<'a>;
+    type Iter = core::str::EncodeUtf16<'a>;
 
     #[inline]
     fn into_wide_iter(self) -> Self::Iter {
@@ -94,7 +318,7 @@ impl<'a> IntoWideIter for &'a String {
 }
 
 impl<'a> IntoWideIter for &'a [u16] {
-    type Iter = std::iter::Copied<std::slice::Iter<'a, u16>>;
+    type Iter = core::iter::Copied<core::slice::Iter<'a, u16>>;
 
     #[inline]
     fn into_wide_iter(self) -> Self::Iter {
@@ -104,8 +328,9 @@ impl<'a> IntoWideIter for &'a [u16] {
 
 // TODO: Remove this in the next version.
 // We do not benefit from taking ownership of the buffer.
+#[cfg(feature = "alloc")]
 impl IntoWideIter for Vec<u16> {
-    type Iter = std::vec::IntoIter<u16>;
+    type Iter = alloc::vec::IntoIter<u16>;
 
     #[inline]
     fn into_wide_iter(self) -> Self::Iter {
@@ -113,8 +338,9 @@ impl IntoWideIter for Vec<u16> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a> IntoWideIter for &'a Vec<u16> {
-    type Iter = std::iter::Copied<std::slice::Iter<'a, u16>>;
+    type Iter = core::iter::Copied<core::slice::Iter<'a, u16>>;
 
     #[inline]
     fn into_wide_iter(self) -> Self::Iter {
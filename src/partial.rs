@@ -0,0 +1,62 @@
+use crate::decompress::decompress_tokens;
+use crate::encoding::Encoding;
+use crate::token::TokenError;
+use crate::token::Tokenizer;
+use crate::IntoWideIter;
+use alloc::vec::Vec;
+
+/// The result of [`decompress_partial`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialDecompress {
+    /// The data produced by every code before the one decompression stopped at.
+    pub output: Vec<u16>,
+
+    /// Why decompression stopped early, or `None` if the whole stream was decompressed.
+    pub error: Option<TokenError>,
+
+    /// The index of the char in which the code decompression stopped at starts.
+    ///
+    /// This is the close code if the whole stream was decompressed.
+    /// Chars that are not part of the encoding's alphabet are skipped and not counted.
+    pub position: usize,
+}
+
+impl PartialDecompress {
+    /// Whether the whole stream was decompressed.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Decompress as much of a truncated or damaged stream as possible.
+///
+/// Decompression stops at the first code that is cut off or does not refer to a valid entry,
+/// keeping the output of every code before it.
+/// Damage that still produces valid codes cannot be detected,
+/// so the output may be wrong after the first damaged char.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::{Encoding, TokenError};
+/// let compressed = lz_str::compress_to_encoded_uri_component("The quick brown fox jumps over the lazy dog");
+/// let truncated = &compressed[..compressed.len() / 2];
+///
+/// let partial = lz_str::decompress_partial(truncated, Encoding::EncodedUriComponent);
+/// assert_eq!(partial.error, Some(TokenError::UnexpectedEnd));
+/// assert!(String::from_utf16(&partial.output).unwrap().starts_with("The quick"));
+/// ```
+pub fn decompress_partial(compressed: impl IntoWideIter, encoding: Encoding) -> PartialDecompress {
+    let compressed = encoding.values(compressed.into_wide_iter());
+    let bits_per_char = encoding.bits_per_char();
+
+    let mut output = Vec::new();
+    let (bit_position, result) =
+        decompress_tokens(&mut Tokenizer::new(compressed, bits_per_char), &mut output);
+
+    PartialDecompress {
+        output,
+        error: result.err(),
+        position: bit_position / usize::from(bits_per_char),
+    }
+}
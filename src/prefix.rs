@@ -0,0 +1,185 @@
+use crate::decompress::decompress_tokens;
+use crate::encoding::Encoding;
+use crate::token::TokenError;
+use crate::token::Tokenizer;
+use crate::IntoWideIter;
+use alloc::vec::Vec;
+
+/// Get the values of the chars that are part of the alphabet, along with the index of each char.
+fn indexed_values(chars: &[u16], encoding: Encoding) -> Vec<(usize, u16)> {
+    chars
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &c)| Some((i, encoding.char_to_value(c)?)))
+        .collect()
+}
+
+/// Decompress the stream that starts at the first of `values`.
+///
+/// Returns the decompressed data and the index of the char after the stream and its padding.
+fn decompress_stream(
+    chars: &[u16],
+    values: &[(usize, u16)],
+    encoding: Encoding,
+) -> Result<(Vec<u16>, usize), TokenError> {
+    let bits_per_char = encoding.bits_per_char();
+
+    let mut tokenizer = Tokenizer::new(values.iter().map(|&(_, value)| value), bits_per_char);
+    let mut output = Vec::new();
+    decompress_tokens(&mut tokenizer, &mut output).1?;
+
+    // The compressor always writes at least one bit of padding after the close code.
+    let consumed = tokenizer.bit_position() / usize::from(bits_per_char) + 1;
+    let end = match values.get(consumed - 1).or_else(|| values.last()) {
+        Some(&(i, _)) => i + 1,
+        // An empty input has no close code.
+        None => chars.len(),
+    };
+
+    Ok((output, end + encoding.padding_len(&chars[end..])))
+}
+
+/// Decompress the stream at the start of the data, ignoring anything after it.
+///
+/// Returns the decompressed data and the number of chars the stream took,
+/// including the encoding's padding if it is there.
+/// Chars that are not part of the encoding's alphabet are skipped,
+/// so the ones inside the stream are counted too.
+///
+/// # Errors
+/// Returns `None` if the compressed data could not be decompressed.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::Encoding;
+/// let mut compressed = lz_str::compress_to_base64("The quick brown fox");
+/// let len = compressed.len();
+/// compressed.push_str("junk");
+///
+/// let (decompressed, consumed) = lz_str::decompress_prefix(&compressed, Encoding::Base64)
+///     .expect("invalid data");
+/// assert_eq!(String::from_utf16(&decompressed).unwrap(), "The quick brown fox");
+/// assert_eq!(consumed, len);
+/// ```
+pub fn decompress_prefix(
+    compressed: impl IntoWideIter,
+    encoding: Encoding,
+) -> Option<(Vec<u16>, usize)> {
+    let chars: Vec<u16> = compressed.into_wide_iter().collect();
+    let values = indexed_values(&chars, encoding);
+
+    decompress_stream(&chars, &values, encoding).ok()
+}
+
+/// Decompress the data, rejecting anything after the stream besides the encoding's padding.
+///
+/// # Errors
+/// Returns `None` if the compressed data could not be decompressed,
+/// or if there is data after the stream.
+pub fn decompress_strict(compressed: impl IntoWideIter, encoding: Encoding) -> Option<Vec<u16>> {
+    let chars: Vec<u16> = compressed.into_wide_iter().collect();
+
+    match decompress_whole(&chars, encoding).ok()? {
+        (decompressed, false) => Some(decompressed),
+        (_, true) => None,
+    }
+}
+
+/// Decompress the stream at the start of the chars.
+///
+/// Returns the decompressed data and whether anything besides the encoding's padding follows the stream.
+pub(crate) fn decompress_whole(
+    chars: &[u16],
+    encoding: Encoding,
+) -> Result<(Vec<u16>, bool), TokenError> {
+    let values = indexed_values(chars, encoding);
+
+    let (decompressed, consumed) = decompress_stream(chars, &values, encoding)?;
+    Ok((decompressed, consumed != chars.len()))
+}
+
+/// An iterator over the decompressed data of streams that follow each other.
+///
+/// This is created by [`decompress_streams`].
+/// It stops after the first stream that could not be decompressed.
+#[derive(Debug, Clone)]
+pub struct Streams {
+    chars: Vec<u16>,
+    values: Vec<(usize, u16)>,
+    encoding: Encoding,
+
+    /// The index in `values` of the start of the next stream.
+    next: usize,
+
+    /// The number of chars that were consumed.
+    position: usize,
+
+    done: bool,
+}
+
+impl Streams {
+    /// The number of chars taken by the streams that were decompressed so far,
+    /// including their padding.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Iterator for Streams {
+    type Item = Result<Vec<u16>, TokenError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.next == self.values.len() {
+            return None;
+        }
+
+        let values = &self.values[self.next..];
+        match decompress_stream(&self.chars, values, self.encoding) {
+            Ok((decompressed, end)) => {
+                self.next += values.partition_point(|&(i, _)| i < end);
+                self.position = end;
+                Some(Ok(decompressed))
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+impl core::iter::FusedIterator for Streams {}
+
+/// Decompress several streams that were concatenated together.
+///
+/// Each stream may be followed by the encoding's padding.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::Encoding;
+/// let mut compressed = lz_str::compress_to_utf16("first");
+/// compressed.push_str(&lz_str::compress_to_utf16("second"));
+///
+/// let streams: Result<Vec<Vec<u16>>, _> =
+///     lz_str::decompress_streams(&compressed, Encoding::Utf16).collect();
+/// let streams: Vec<String> = streams
+///     .unwrap()
+///     .into_iter()
+///     .map(|stream| String::from_utf16(&stream).unwrap())
+///     .collect();
+/// assert_eq!(streams, ["first", "second"]);
+/// ```
+pub fn decompress_streams(compressed: impl IntoWideIter, encoding: Encoding) -> Streams {
+    let chars: Vec<u16> = compressed.into_wide_iter().collect();
+    let values = indexed_values(&chars, encoding);
+
+    Streams {
+        chars,
+        values,
+        encoding,
+        next: 0,
+        position: 0,
+        done: false,
+    }
+}
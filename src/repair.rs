@@ -0,0 +1,285 @@
+use crate::compress::compress_with_encoding;
+use crate::decompress::decompress_tokens;
+use crate::encoding::Encoding;
+use crate::partial::decompress_partial;
+use crate::token::Tokenizer;
+use crate::IntoWideIter;
+use alloc::vec::Vec;
+
+/// How many chars before where decompression breaks edits are tried at.
+///
+/// Each candidate is decompressed and compressed again,
+/// so searching the whole payload would take quadratic time.
+/// Damage almost always breaks decompression within a few codes.
+const SEARCH_WINDOW: usize = 256;
+
+/// A single char edit of a payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edit {
+    /// Replace a char.
+    Substitute {
+        /// The index of the char in the payload.
+        position: usize,
+
+        /// The new char.
+        char: u16,
+    },
+
+    /// Insert a char.
+    Insert {
+        /// The index in the payload the char is inserted at.
+        position: usize,
+
+        /// The new char.
+        char: u16,
+    },
+
+    /// Remove a char.
+    Delete {
+        /// The index of the char in the payload.
+        position: usize,
+    },
+}
+
+impl Edit {
+    /// The index in the payload this edit is at.
+    #[inline]
+    pub fn position(self) -> usize {
+        match self {
+            Self::Substitute { position, .. }
+            | Self::Insert { position, .. }
+            | Self::Delete { position } => position,
+        }
+    }
+
+    /// Apply this edit to a payload.
+    ///
+    /// # Panics
+    /// Panics if the position is out of bounds.
+    pub fn apply(self, payload: &[u16]) -> Vec<u16> {
+        let mut payload = payload.to_vec();
+        match self {
+            Self::Substitute { position, char } => payload[position] = char,
+            Self::Insert { position, char } => payload.insert(position, char),
+            Self::Delete { position } => {
+                payload.remove(position);
+            }
+        }
+        payload
+    }
+}
+
+/// A candidate repair of a payload, from [`repair`] or [`repair_with_validator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    /// The edit that was made.
+    pub edit: Edit,
+
+    /// The repaired payload.
+    pub payload: Vec<u16>,
+
+    /// The decompressed data of the repaired payload.
+    pub output: Vec<u16>,
+
+    /// Whether compressing the output again gives the repaired payload.
+    ///
+    /// Payloads from this library or the reference implementation with the default level are canonical,
+    /// so a canonical repair is much more likely to be the right one.
+    pub canonical: bool,
+}
+
+/// Search for single char edits that make a payload decompress.
+///
+/// See [`repair_with_validator`].
+pub fn repair(payload: impl IntoWideIter, encoding: Encoding) -> Vec<Repair> {
+    repair_with_validator(payload, encoding, |_| true)
+}
+
+/// Search for single char edits that make a payload decompress to data accepted by a validator.
+///
+/// A repaired payload must decompress completely and end right after its close code,
+/// with the padding a compressor would have written.
+/// Damage is always at or before the code where decompression breaks,
+/// so only edits up to that point are tried, nearest first.
+/// Payloads that decompress but fail the validator are searched from their end.
+/// Only the last 256 chars before that point are searched,
+/// so damage further back is not found.
+///
+/// Every char of the alphabet is tried for the 6 bit encodings.
+/// The wide encodings have too many chars for that,
+/// so only substitutions that flip a single bit are tried, along with deletions.
+/// Insertions are never tried for them, so a missing char in those payloads is not found.
+///
+/// Repairs are ranked with canonical ones first, then by their distance from where decompression broke.
+/// Returns no repairs if the payload is already valid.
+///
+/// # Performance
+/// Each candidate is decompressed, and compressed again if it decompresses,
+/// so this takes O(n) time for each of them, for a payload of n chars.
+/// The 6 bit encodings try up to 129 candidates at each of the up to 256 positions searched,
+/// which is over 30,000 passes over the payload.
+/// The wide encodings try up to 17 at each position.
+/// This is only suitable for short payloads, like the ones in links.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::Encoding;
+/// let compressed: Vec<u16> = lz_str::compress_to_encoded_uri_component("{\"page\":3,\"theme\":\"dark\"}")
+///     .encode_utf16()
+///     .collect();
+///
+/// // A char was lost when the link was copied.
+/// let mut damaged = compressed.clone();
+/// damaged.remove(5);
+///
+/// let repairs = lz_str::repair_with_validator(&damaged, Encoding::EncodedUriComponent, |output| {
+///     String::from_utf16(output).map_or(false, |s| s.starts_with('{') && s.ends_with('}'))
+/// });
+/// assert!(repairs.iter().any(|repair| repair.payload == compressed));
+/// ```
+pub fn repair_with_validator<F>(
+    payload: impl IntoWideIter,
+    encoding: Encoding,
+    validator: F,
+) -> Vec<Repair>
+where
+    F: Fn(&[u16]) -> bool,
+{
+    let payload: Vec<u16> = payload.into_wide_iter().collect();
+    if let Some((output, _)) = check(&payload, encoding) {
+        if validator(&output) {
+            return Vec::new();
+        }
+    }
+
+    let bits_per_char = encoding.bits_per_char();
+
+    // Find where decompression breaks, in chars of the payload.
+    // The failing code may continue for a few chars after the one it starts in.
+    let partial = decompress_partial(&payload, encoding);
+    let (break_position, search_end) = if partial.is_complete() {
+        (payload.len(), payload.len())
+    } else {
+        let code_chars = 32 / usize::from(bits_per_char) + 1;
+        (
+            payload_position(&payload, encoding, partial.position),
+            payload_position(&payload, encoding, partial.position + code_chars),
+        )
+    };
+
+    let search_start = break_position.saturating_sub(SEARCH_WINDOW);
+    let mut positions: Vec<usize> = (search_start..=search_end).collect();
+    positions.sort_by_key(|&position| position.abs_diff(break_position));
+
+    // The 6 bit encodings have small alphabets.
+    let alphabet: Vec<u16> = if bits_per_char <= 8 {
+        (0..1_u16 << bits_per_char)
+            .map(|n| encoding.value_to_char(n))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // Inserting or deleting a char next to an equal char gives the same payload
+    // as doing it at the first char of their run, so only that edit is tried.
+    let repeats =
+        |position: usize, c: u16| position > search_start && payload.get(position - 1) == Some(&c);
+
+    let mut repairs = Vec::new();
+    let mut edits = Vec::new();
+    for position in positions {
+        edits.clear();
+        if let Some(&c) = payload.get(position) {
+            if alphabet.is_empty() {
+                if let Some(value) = encoding.char_to_value(c) {
+                    // Flipping a bit never gives the same char.
+                    for bit in 0..bits_per_char {
+                        let value = value ^ (1 << bit);
+                        if u32::from(value) >> bits_per_char == 0 {
+                            edits.push(Edit::Substitute {
+                                position,
+                                char: encoding.value_to_char(value),
+                            });
+                        }
+                    }
+                }
+            } else {
+                edits.extend(
+                    alphabet
+                        .iter()
+                        .filter(|&&char| char != c)
+                        .map(|&char| Edit::Substitute { position, char }),
+                );
+            }
+
+            if !repeats(position, c) {
+                edits.push(Edit::Delete { position });
+            }
+        }
+        edits.extend(
+            alphabet
+                .iter()
+                .filter(|&&char| !repeats(position, char))
+                .map(|&char| Edit::Insert { position, char }),
+        );
+
+        for &edit in edits.iter() {
+            let candidate = edit.apply(&payload);
+            if let Some((output, canonical)) = check(&candidate, encoding) {
+                if validator(&output) {
+                    repairs.push(Repair {
+                        edit,
+                        payload: candidate,
+                        output,
+                        canonical,
+                    });
+                }
+            }
+        }
+    }
+
+    // The sort is stable, so repairs stay ordered by distance.
+    repairs.sort_by_key(|repair| !repair.canonical);
+
+    repairs
+}
+
+/// Get the index in the payload of the char with the given index in the bit stream.
+fn payload_position(payload: &[u16], encoding: Encoding, value_position: usize) -> usize {
+    payload
+        .iter()
+        .enumerate()
+        .filter(|(_, &c)| encoding.char_to_value(c).is_some())
+        .nth(value_position)
+        .map_or(payload.len(), |(i, _)| i)
+}
+
+/// Decompress a payload, checking that it ends like a compressor would end it.
+///
+/// Returns the decompressed data and whether compressing it again gives the same payload.
+fn check(payload: &[u16], encoding: Encoding) -> Option<(Vec<u16>, bool)> {
+    let values: Vec<u16> = encoding.values(payload.iter().copied()).collect();
+    let bits_per_char = usize::from(encoding.bits_per_char());
+
+    let mut tokenizer = Tokenizer::new(values.iter().copied(), encoding.bits_per_char());
+    let mut output = Vec::new();
+    decompress_tokens(&mut tokenizer, &mut output).1.ok()?;
+
+    // The compressor always writes at least one bit of padding after the close code,
+    // and pads with zeros.
+    let end = tokenizer.bit_position();
+    let (data, padding) = values.split_at((end / bits_per_char + 1).min(values.len()));
+    if data.len() * bits_per_char <= end || padding != encoding.padding_values(data.len()) {
+        return None;
+    }
+    let padding_mask = (1_u32 << (data.len() * bits_per_char - end)) - 1;
+    if u32::from(*data.last()?) & padding_mask != 0 {
+        return None;
+    }
+
+    let canonical = encoding
+        .values(compress_with_encoding(&output, encoding).into_iter())
+        .eq(values.iter().copied());
+
+    Some((output, canonical))
+}
@@ -0,0 +1,239 @@
+use crate::decompress::decompressed_len;
+use crate::dictionary::LinkedDictionary;
+use crate::encoding::Encoding;
+use crate::token::Token;
+use crate::token::Tokenizer;
+use crate::IntoWideIter;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// A missing entry link.
+const NONE: u32 = u32::MAX;
+
+/// A Knuth-Morris-Pratt matcher for the pattern.
+#[derive(Debug)]
+struct Matcher {
+    pattern: Vec<u16>,
+
+    /// The length of the longest proper border of each prefix of the pattern.
+    failure: Vec<usize>,
+}
+
+impl Matcher {
+    fn new(pattern: Vec<u16>) -> Self {
+        let mut failure = vec![0; pattern.len()];
+        let mut border = 0;
+        for i in 1..pattern.len() {
+            while border > 0 && pattern[i] != pattern[border] {
+                border = failure[border - 1];
+            }
+            if pattern[i] == pattern[border] {
+                border += 1;
+            }
+            failure[i] = border;
+        }
+
+        Matcher { pattern, failure }
+    }
+
+    /// Get the length of the matched prefix of the pattern after reading a char.
+    #[inline]
+    fn step(&self, mut state: usize, c: u16) -> usize {
+        loop {
+            if state < self.pattern.len() && self.pattern[state] == c {
+                return state + 1;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = self.failure[state - 1];
+        }
+    }
+}
+
+/// What the matcher knows about a dictionary entry.
+#[derive(Debug, Clone, Copy)]
+struct EntryInfo {
+    /// The matcher's state after reading only this entry.
+    state: usize,
+
+    /// The longest prefix of this entry, including itself, that ends with the pattern.
+    occurrence: u32,
+
+    /// The prefix of this entry with `pattern.len() - 1` chars, or the entry itself if it is shorter.
+    head: u32,
+}
+
+/// Search for a pattern in compressed data,
+/// calling `on_match` with the start of each match until it returns `false`.
+///
+/// This follows the code stream, reusing what is known about each dictionary entry.
+/// Only matches that cross the start of a code need to be found by reading chars,
+/// and those never need more than the first `pattern.len() - 1` chars of the entry.
+fn search<F>(
+    compressed: impl IntoWideIter,
+    encoding: Encoding,
+    pattern: Vec<u16>,
+    mut on_match: F,
+) -> Option<()>
+where
+    F: FnMut(usize) -> bool,
+{
+    let pattern_len = pattern.len();
+    let matcher = Matcher::new(pattern);
+    let compressed = encoding.values(compressed.into_wide_iter());
+
+    let mut dictionary = LinkedDictionary::new();
+    let placeholder = EntryInfo {
+        state: 0,
+        occurrence: NONE,
+        head: NONE,
+    };
+    let mut entries = vec![placeholder; dictionary.len()];
+
+    let mut state = 0;
+    let mut position: usize = 0;
+    let mut chars = Vec::with_capacity(pattern_len);
+    let mut occurrences = Vec::new();
+    for token in Tokenizer::new(compressed, encoding.bits_per_char()) {
+        let token = token.ok()?;
+        if token == Token::Close {
+            break;
+        }
+
+        let code = dictionary.push(token)?;
+
+        // Learn about the new entries.
+        while entries.len() < dictionary.len() {
+            // This fits, as the dictionary has at most u32::MAX entries.
+            let new_code = u32::try_from(entries.len()).unwrap();
+            let entry = dictionary.get(new_code)?;
+            let prefix = match entry.prefix() {
+                Some(prefix) => entries[usize::try_from(prefix).ok()?],
+                None => placeholder,
+            };
+
+            let entry_state = matcher.step(prefix.state, entry.last);
+            entries.push(EntryInfo {
+                state: entry_state,
+                occurrence: if entry_state == pattern_len {
+                    new_code
+                } else {
+                    prefix.occurrence
+                },
+                head: if usize::try_from(entry.len).ok()? < pattern_len {
+                    new_code
+                } else {
+                    prefix.head
+                },
+            });
+        }
+
+        let entry = dictionary.get(code)?;
+        let entry_len = usize::try_from(entry.len).ok()?;
+        let info = entries[usize::try_from(code).ok()?];
+
+        // Matches that start before this entry end within its first `pattern_len - 1` chars.
+        chars.clear();
+        if info.head != NONE {
+            let head_len = dictionary.get(info.head)?.len;
+            dictionary.extend_from_entry(info.head, 0, head_len, &mut chars);
+        }
+        for (i, &c) in chars.iter().enumerate() {
+            state = matcher.step(state, c);
+            if state == pattern_len && !on_match(position + i + 1 - pattern_len) {
+                return Some(());
+            }
+        }
+
+        // Matches inside this entry are the ones that end each of its prefixes.
+        occurrences.clear();
+        let mut occurrence = info.occurrence;
+        while occurrence != NONE {
+            let occurrence_entry = dictionary.get(occurrence)?;
+            occurrences.push(position + usize::try_from(occurrence_entry.len).ok()? - pattern_len);
+
+            occurrence = match occurrence_entry.prefix() {
+                Some(prefix) => entries[usize::try_from(prefix).ok()?].occurrence,
+                None => NONE,
+            };
+        }
+        for &start in occurrences.iter().rev() {
+            if !on_match(start) {
+                return Some(());
+            }
+        }
+
+        // An entry at least as long as the pattern hides whatever came before it.
+        if entry_len >= pattern_len {
+            state = info.state;
+        }
+
+        position = position.checked_add(entry_len)?;
+    }
+
+    Some(())
+}
+
+/// Check whether compressed data contains a pattern, without decompressing it.
+///
+/// The search stops at the first match,
+/// so data after it is not checked for errors.
+///
+/// # Errors
+/// Returns `None` if the compressed data could not be decompressed.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::Encoding;
+/// let compressed = lz_str::compress_to_base64("The quick brown fox jumps over the lazy dog");
+/// assert_eq!(lz_str::compressed_contains(&compressed, Encoding::Base64, "fox"), Some(true));
+/// assert_eq!(lz_str::compressed_contains(&compressed, Encoding::Base64, "cat"), Some(false));
+/// ```
+pub fn compressed_contains(
+    compressed: impl IntoWideIter,
+    encoding: Encoding,
+    pattern: impl IntoWideIter,
+) -> Option<bool> {
+    let pattern: Vec<u16> = pattern.into_wide_iter().collect();
+    if pattern.is_empty() {
+        return decompressed_len(encoding, compressed).map(|_| true);
+    }
+
+    let mut found = false;
+    search(compressed, encoding, pattern, |_| {
+        found = true;
+        false
+    })?;
+
+    Some(found)
+}
+
+/// Find the start of every match of a pattern in compressed data, without decompressing it.
+///
+/// Positions are in `u16`s of the decompressed data, in ascending order.
+/// Overlapping matches are included.
+/// An empty pattern has no matches,
+/// as listing every position would take memory proportional to the claimed decompressed length.
+///
+/// # Errors
+/// Returns `None` if the compressed data could not be decompressed.
+pub fn compressed_find_all(
+    compressed: impl IntoWideIter,
+    encoding: Encoding,
+    pattern: impl IntoWideIter,
+) -> Option<Vec<usize>> {
+    let pattern: Vec<u16> = pattern.into_wide_iter().collect();
+    if pattern.is_empty() {
+        return decompressed_len(encoding, compressed).map(|_| Vec::new());
+    }
+
+    let mut positions = Vec::new();
+    search(compressed, encoding, pattern, |position| {
+        positions.push(position);
+        true
+    })?;
+
+    Some(positions)
+}
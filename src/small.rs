@@ -0,0 +1,454 @@
+//! Storage on the stack for small inputs, so they can be compressed and decompressed without
+//! allocating anything besides the result.
+//!
+//! The buffers are sized for the input, so they stay small enough for small thread and task stacks.
+//! With `std`, compressing an input of up to 64 chars puts under 4 KiB of buffers on the stack,
+//! and an input of up to [`SMALL_LEN`] chars under 10 KiB.
+//! Without `std`, only inputs of up to 64 chars are compressed on the stack, with under 2.5 KiB.
+//! Decompressing puts under 2 KiB of buffers on the stack, which move to the heap once they are full.
+
+use crate::compress::BitWriter;
+use crate::constants::CLOSE_CODE;
+use crate::constants::NUM_BASE_CODES;
+use crate::constants::START_CODE_BITS;
+use crate::constants::U16_CODE;
+use crate::constants::U8_CODE;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::iter::FromIterator;
+use core::ops::Range;
+
+/// The most chars an input can have to be compressed by [`compress_small`].
+///
+/// Without `std`, the stack may be much smaller, so less is kept on it.
+#[cfg(feature = "std")]
+pub(crate) const SMALL_LEN: usize = 256;
+#[cfg(not(feature = "std"))]
+pub(crate) const SMALL_LEN: usize = 64;
+
+/// The number of chars [`compress_small`] holds inline before moving its output to the heap.
+pub(crate) const SMALL_OUTPUT_LEN: usize = 2 * SMALL_LEN;
+
+/// The number of dictionary entries the decompressor holds inline before moving them to the heap.
+pub(crate) const SMALL_ENTRIES: usize = 64;
+
+/// The most chars an input can have to use the smaller dictionary of [`compress_small`].
+///
+/// Filling the larger dictionary would take longer than compressing such inputs.
+const TINY_LEN: usize = 64;
+
+/// The most slots [`SmallDictionary::find`] looks at before giving up.
+///
+/// The hash is fixed, so inputs can be crafted to put all of their entries in one run of slots.
+/// Compressing those with the dictionary of [`crate::compress::CompressContext`] instead,
+/// which uses a DoS-resistant hasher by default, keeps them from taking quadratic time.
+const MAX_PROBES: usize = 32;
+
+/// A vector that holds up to `N` items inline, moving them to the heap when it grows past that.
+#[derive(Debug, Clone)]
+pub(crate) enum InlineVec<T, const N: usize> {
+    Inline { items: [T; N], len: usize },
+    Heap(Vec<T>),
+}
+
+impl<T, const N: usize> InlineVec<T, N>
+where
+    T: Copy + Default,
+{
+    /// Make a new, empty [`InlineVec`].
+    #[inline]
+    pub fn new() -> Self {
+        Self::Inline {
+            items: [T::default(); N],
+            len: 0,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        match self {
+            Self::Inline { items, len } => &items[..*len],
+            Self::Heap(vec) => vec,
+        }
+    }
+
+    #[inline]
+    pub fn push(&mut self, item: T) {
+        if let Self::Inline { items, len } = self {
+            if let Some(slot) = items.get_mut(*len) {
+                *slot = item;
+                *len += 1;
+                return;
+            }
+            self.spill(1);
+        }
+
+        if let Self::Heap(vec) = self {
+            vec.push(item);
+        }
+    }
+
+    /// Copy the items in `range` to the end.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds.
+    #[inline]
+    pub fn extend_from_within(&mut self, range: Range<usize>) {
+        if let Self::Inline { items, len } = self {
+            assert!(range.end <= *len);
+
+            let end = *len + range.len();
+            if end <= N {
+                items.copy_within(range, *len);
+                *len = end;
+                return;
+            }
+            self.spill(range.len());
+        }
+
+        if let Self::Heap(vec) = self {
+            vec.extend_from_within(range);
+        }
+    }
+
+    /// Move the items to the heap, with room for at least `additional` more.
+    fn spill(&mut self, additional: usize) {
+        if let Self::Inline { items, len } = self {
+            let mut vec = Vec::with_capacity((2 * N).max(*len + additional));
+            vec.extend_from_slice(&items[..*len]);
+            *self = Self::Heap(vec);
+        }
+    }
+
+    /// Get the items as a [`Vec`], with an allocation of the exact size if they are inline.
+    #[inline]
+    pub fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::Inline { items, len } => items[..len].to_vec(),
+            Self::Heap(vec) => vec,
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for InlineVec<T, N>
+where
+    T: Copy + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let iter = iter.into_iter();
+        if iter.size_hint().0 > N {
+            return Self::Heap(iter.collect());
+        }
+
+        let mut vec = Self::new();
+        for item in iter {
+            vec.push(item);
+        }
+        vec
+    }
+}
+
+/// A slot of a [`SmallDictionary`].
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    /// The key of the entry, from [`entry_key`], or [`Slot::EMPTY_KEY`].
+    key: u32,
+
+    /// The code of the entry.
+    code: u16,
+
+    /// Whether this is a single char entry whose char was not written yet.
+    pending: bool,
+}
+
+impl Slot {
+    /// The key of an empty slot.
+    ///
+    /// Codes are much smaller than `u16::MAX`, so no entry has this key.
+    const EMPTY_KEY: u32 = u32::MAX;
+
+    const EMPTY: Self = Slot {
+        key: Self::EMPTY_KEY,
+        code: 0,
+        pending: false,
+    };
+}
+
+/// Get the key of an entry that is the entry of `prefix` plus `c`, or just `c`.
+#[inline]
+fn entry_key(prefix: Option<u16>, c: u16) -> u32 {
+    let prefix = prefix.map_or(0, |code| u32::from(code) + 1);
+    (prefix << 16) | u32::from(c)
+}
+
+/// The compressor's dictionary for inputs of at most [`SMALL_LEN`] chars.
+///
+/// Instead of mapping runs of the input to codes,
+/// it maps each entry as the code of the entry without its last char plus that char.
+/// It is a hash table with linear probing that never grows, as the input is small.
+/// Each char of the input adds at most 2 entries,
+/// so there must be at least 4 times as many slots as chars to keep at least half of them empty.
+///
+/// `SLOTS` must be a power of two.
+#[derive(Debug)]
+struct SmallDictionary<const SLOTS: usize> {
+    slots: [Slot; SLOTS],
+    next_code: u16,
+}
+
+impl<const SLOTS: usize> SmallDictionary<SLOTS> {
+    fn new() -> Self {
+        SmallDictionary {
+            slots: [Slot::EMPTY; SLOTS],
+            next_code: NUM_BASE_CODES.into(),
+        }
+    }
+
+    /// Get the index of the slot of an entry, or of the empty slot it would go in.
+    ///
+    /// Returns `None` if neither is found within [`MAX_PROBES`] slots.
+    #[inline]
+    fn find(&self, key: u32) -> Option<(usize, bool)> {
+        let hash = key.wrapping_mul(0x9E37_79B9) >> (32 - SLOTS.trailing_zeros());
+        let mut index = usize::try_from(hash).unwrap();
+        for _ in 0..MAX_PROBES {
+            match self.slots[index].key {
+                Slot::EMPTY_KEY => return Some((index, false)),
+                slot_key if slot_key == key => return Some((index, true)),
+                _ => index = (index + 1) % SLOTS,
+            }
+        }
+
+        None
+    }
+
+    /// Add an entry in an empty slot from [`SmallDictionary::find`].
+    #[inline]
+    fn insert(&mut self, index: usize, key: u32, pending: bool) {
+        self.slots[index] = Slot {
+            key,
+            code: self.next_code,
+            pending,
+        };
+        self.next_code += 1;
+    }
+}
+
+/// A compressor for inputs of at most [`SMALL_LEN`] chars, which keeps everything on the stack.
+///
+/// The output is identical to [`crate::compress::CompressContext`].
+struct SmallCompressor<F, const SLOTS: usize> {
+    dictionary: SmallDictionary<SLOTS>,
+
+    /// The counter for increasing the current number of bits in a code.
+    enlarge_in: u32,
+
+    /// The current number of bits in a code.
+    num_bits: u8,
+
+    writer: BitWriter<F, InlineVec<u16, SMALL_OUTPUT_LEN>>,
+}
+
+impl<F, const SLOTS: usize> SmallCompressor<F, SLOTS>
+where
+    F: Fn(u16) -> u16,
+{
+    #[inline]
+    fn decrement_enlarge_in(&mut self) {
+        self.enlarge_in -= 1;
+        if self.enlarge_in == 0 {
+            self.enlarge_in = 1 << self.num_bits;
+            self.num_bits += 1;
+        }
+    }
+
+    /// Write the code of the entry in a slot, or its char if it was not written yet.
+    #[inline]
+    fn produce(&mut self, index: usize) {
+        let slot = &mut self.dictionary.slots[index];
+        if slot.pending {
+            slot.pending = false;
+
+            // The key of a single char entry is its char.
+            let c = u16::try_from(slot.key).unwrap();
+            if c < 256 {
+                self.writer.write_bits(self.num_bits, U8_CODE.into());
+                self.writer.write_bits(8, c.into());
+            } else {
+                self.writer.write_bits(self.num_bits, U16_CODE.into());
+                self.writer.write_bits(16, c.into());
+            }
+            self.decrement_enlarge_in();
+        } else {
+            let code = slot.code;
+            self.writer.write_bits(self.num_bits, code.into());
+        }
+        self.decrement_enlarge_in();
+    }
+}
+
+/// Compress an input of at most [`SMALL_LEN`] chars without allocating,
+/// unless the output does not fit in [`SMALL_OUTPUT_LEN`] chars.
+///
+/// Returns `None` if the entries of the input collide too much in the dictionary,
+/// in which case it should be compressed some other way.
+///
+/// # Panics
+/// Panics if the input is too long or if `bits_per_char` exceeds the number of bits in a u16.
+pub(crate) fn compress_small<F>(
+    input: &[u16],
+    bits_per_char: u8,
+    to_char: F,
+) -> Option<InlineVec<u16, SMALL_OUTPUT_LEN>>
+where
+    F: Fn(u16) -> u16,
+{
+    assert!(input.len() <= SMALL_LEN);
+
+    if input.len() <= TINY_LEN {
+        compress_with_slots::<F, { 4 * TINY_LEN }>(input, bits_per_char, to_char)
+    } else {
+        compress_with_slots::<F, { 4 * SMALL_LEN }>(input, bits_per_char, to_char)
+    }
+}
+
+/// Compress an input with a dictionary of `SLOTS` slots.
+#[inline]
+fn compress_with_slots<F, const SLOTS: usize>(
+    input: &[u16],
+    bits_per_char: u8,
+    to_char: F,
+) -> Option<InlineVec<u16, SMALL_OUTPUT_LEN>>
+where
+    F: Fn(u16) -> u16,
+{
+    let mut compressor = SmallCompressor::<F, SLOTS> {
+        dictionary: SmallDictionary::new(),
+        enlarge_in: 2,
+        num_bits: START_CODE_BITS,
+        writer: BitWriter::with_output(InlineVec::new(), bits_per_char, to_char),
+    };
+
+    // The slot of the current word, w.
+    let mut w = None;
+    for &c in input {
+        let c_key = entry_key(None, c);
+        let (c_index, found) = compressor.dictionary.find(c_key)?;
+        if !found {
+            compressor.dictionary.insert(c_index, c_key, true);
+        }
+
+        let w_index = match w {
+            Some(w_index) => w_index,
+            None => {
+                w = Some(c_index);
+                continue;
+            }
+        };
+
+        // wc = w + c.
+        let wc_key = entry_key(Some(compressor.dictionary.slots[w_index].code), c);
+        let (wc_index, found) = compressor.dictionary.find(wc_key)?;
+        if found {
+            w = Some(wc_index);
+        } else {
+            compressor.dictionary.insert(wc_index, wc_key, false);
+            compressor.produce(w_index);
+            w = Some(c_index);
+        }
+    }
+
+    if let Some(w_index) = w {
+        compressor.produce(w_index);
+    }
+
+    // Mark the end of the stream.
+    compressor
+        .writer
+        .write_bits(compressor.num_bits, CLOSE_CODE.into());
+
+    Some(compressor.writer.finish())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::compress::CompressContext;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn compress_small_matches_compress_context() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(49);
+        for len in (0..=SMALL_LEN).step_by(7).chain([SMALL_LEN]) {
+            let alphabet_len = rng.gen_range(1..300);
+            let input: Vec<u16> = (0..len)
+                .map(|_| {
+                    if rng.gen_bool(0.9) {
+                        rng.gen_range(0..alphabet_len)
+                    } else {
+                        rng.gen()
+                    }
+                })
+                .collect();
+
+            for bits_per_char in [1, 6, 15, 16] {
+                let expected = CompressContext::new(&input, bits_per_char, |n| n + 1).compress();
+                let compressed = compress_small(&input, bits_per_char, |n| n + 1)
+                    .expect("random input collided too much");
+                assert_eq!(compressed.as_slice(), expected.as_slice());
+            }
+        }
+    }
+
+    #[test]
+    fn compress_small_gives_up_on_colliding_input() {
+        // Chars that all hash to the first slot of the smaller dictionary.
+        let bits = (4 * TINY_LEN).trailing_zeros();
+        let input: Vec<u16> = (0..=u16::MAX)
+            .filter(|&c| entry_key(None, c).wrapping_mul(0x9E37_79B9) >> (32 - bits) == 0)
+            .take(TINY_LEN)
+            .collect();
+        assert_eq!(input.len(), TINY_LEN);
+
+        assert!(compress_small(&input, 16, |n| n).is_none());
+    }
+
+    #[test]
+    fn stack_buffers_are_small() {
+        use crate::fixed::DictionaryEntry;
+        use core::mem::size_of;
+
+        let output = size_of::<InlineVec<u16, SMALL_OUTPUT_LEN>>();
+        let input = size_of::<InlineVec<u16, SMALL_LEN>>();
+        assert!(size_of::<SmallDictionary<{ 4 * TINY_LEN }>>() + output + input <= 4 * 1024);
+        assert!(size_of::<SmallDictionary<{ 4 * SMALL_LEN }>>() + output + input <= 10 * 1024);
+        #[cfg(not(feature = "std"))]
+        assert!(size_of::<SmallDictionary<{ 4 * SMALL_LEN }>>() + output + input <= 2560);
+
+        let entries = size_of::<InlineVec<DictionaryEntry, SMALL_ENTRIES>>();
+        assert!(entries + input <= 2 * 1024);
+    }
+
+    #[test]
+    fn inline_vec_spills_to_heap() {
+        let mut vec: InlineVec<u16, 4> = (0..3).collect();
+        vec.extend_from_within(1..3);
+        assert!(matches!(vec, InlineVec::Heap(_)));
+        vec.push(5);
+        assert_eq!(vec.as_slice(), [0, 1, 2, 1, 2, 5]);
+        assert_eq!(vec.len(), 6);
+        assert_eq!(vec.into_vec(), [0, 1, 2, 1, 2, 5]);
+
+        let vec: InlineVec<u16, 4> = (0..4).collect();
+        assert!(matches!(vec, InlineVec::Inline { .. }));
+        assert_eq!(vec.into_vec(), [0, 1, 2, 3]);
+    }
+}
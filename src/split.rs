@@ -0,0 +1,239 @@
+use crate::crc32::Crc32;
+use crate::encoding::Encoding;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// The number of chars in the header of a part.
+const HEADER_LEN: usize = 2 + 2 + 6;
+
+/// The most parts a payload can be split into, as the count is stored in 2 chars.
+const MAX_PARTS: usize = 1 << 12;
+
+/// Write a number as `len` chars of the encoding's alphabet, most significant bits first.
+fn write_number(encoding: Encoding, n: u64, len: usize, output: &mut String) {
+    for i in (0..len).rev() {
+        // The mask keeps 6 bits.
+        let value = u16::try_from((n >> (i * 6)) & 0x3F).unwrap();
+        // The alphabets are ASCII.
+        output.push(char::from(
+            u8::try_from(encoding.value_to_char(value)).unwrap(),
+        ));
+    }
+}
+
+/// Read a number written by [`write_number`].
+fn read_number(encoding: Encoding, chars: &[u16]) -> Option<u64> {
+    chars.iter().try_fold(0, |n, &c| {
+        let value = encoding.char_to_value(c).filter(|&value| value < 64)?;
+        Some((n << 6) | u64::from(value))
+    })
+}
+
+/// Get the checksum of a payload.
+fn checksum(payload: &str) -> u32 {
+    let mut crc32 = Crc32::new();
+    crc32.update(payload.as_bytes());
+    crc32.finish()
+}
+
+/// Split a compressed payload into parts that are at most `max_len` chars long.
+///
+/// Only [`Encoding::EncodedUriComponent`] and [`Encoding::Base64`] are supported,
+/// so that every part only has chars of the payload's alphabet.
+/// Each part starts with a header of 10 chars in that alphabet,
+/// with the number of parts, the index of the part, and the CRC-32 of the whole payload.
+/// The rest of the part is the next slice of the payload.
+///
+/// Returns `None` if the encoding is not supported,
+/// if `max_len` does not leave room for the header and at least one char,
+/// or if the payload would need more than 4096 parts.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::{Encoding, Reassembler};
+/// let compressed = lz_str::compress_to_encoded_uri_component(&"The quick brown fox. ".repeat(20));
+/// let parts = lz_str::split_payload(&compressed, Encoding::EncodedUriComponent, 30)
+///     .expect("cannot split");
+/// assert!(parts.iter().all(|part| part.len() <= 30));
+///
+/// let mut reassembler = Reassembler::new(Encoding::EncodedUriComponent);
+/// for part in parts.iter().rev() {
+///     reassembler.push(part).expect("invalid part");
+/// }
+/// assert_eq!(reassembler.finish(), Ok(compressed));
+/// ```
+pub fn split_payload(payload: &str, encoding: Encoding, max_len: usize) -> Option<Vec<String>> {
+    if !matches!(encoding, Encoding::EncodedUriComponent | Encoding::Base64)
+        || max_len <= HEADER_LEN
+    {
+        return None;
+    }
+
+    let chars: Vec<char> = payload.chars().collect();
+    let part_len = max_len - HEADER_LEN;
+    let count = chars.len().div_ceil(part_len).max(1);
+    if count > MAX_PARTS {
+        return None;
+    }
+
+    let crc32 = checksum(payload);
+    let mut parts = Vec::with_capacity(count);
+    for index in 0..count {
+        let start = (index * part_len).min(chars.len());
+        let end = (start + part_len).min(chars.len());
+
+        let mut part = String::with_capacity(max_len);
+        // Both are less than MAX_PARTS.
+        write_number(encoding, u64::try_from(count - 1).unwrap(), 2, &mut part);
+        write_number(encoding, u64::try_from(index).unwrap(), 2, &mut part);
+        write_number(encoding, u64::from(crc32), 6, &mut part);
+        part.extend(&chars[start..end]);
+
+        parts.push(part);
+    }
+
+    Some(parts)
+}
+
+/// An error from reassembling parts from [`split_payload`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReassembleError {
+    /// The part does not have a valid header.
+    InvalidHeader,
+
+    /// The part is from a different payload than the other parts.
+    Mismatch,
+
+    /// Some parts have not been received, listed by their index.
+    Missing(Vec<usize>),
+
+    /// The reassembled payload does not match the checksum in the headers.
+    ChecksumMismatch,
+}
+
+impl core::fmt::Display for ReassembleError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "the part does not have a valid header"),
+            Self::Mismatch => write!(f, "the part is from a different payload"),
+            Self::Missing(missing) => write!(f, "missing {} parts", missing.len()),
+            Self::ChecksumMismatch => write!(f, "the payload does not match its checksum"),
+        }
+    }
+}
+
+impl core::error::Error for ReassembleError {}
+
+/// Reassemble a payload from parts made by [`split_payload`], in any order.
+#[derive(Debug, Clone)]
+pub struct Reassembler {
+    encoding: Encoding,
+
+    /// The checksum of the payload, from the first part.
+    crc32: Option<u32>,
+
+    parts: Vec<Option<String>>,
+}
+
+impl Reassembler {
+    /// Make a new [`Reassembler`] for parts of the given encoding.
+    pub fn new(encoding: Encoding) -> Self {
+        Reassembler {
+            encoding,
+            crc32: None,
+            parts: Vec::new(),
+        }
+    }
+
+    /// Add a part.
+    ///
+    /// Adding a part that was already added replaces it.
+    ///
+    /// # Errors
+    /// Returns an error if the part does not have a valid header,
+    /// or if it is from a different payload than the parts before it.
+    pub fn push(&mut self, part: &str) -> Result<(), ReassembleError> {
+        let chars: Vec<u16> = part.encode_utf16().take(HEADER_LEN).collect();
+        if chars.len() != HEADER_LEN {
+            return Err(ReassembleError::InvalidHeader);
+        }
+
+        let header = |range: core::ops::Range<usize>| {
+            read_number(self.encoding, &chars[range]).ok_or(ReassembleError::InvalidHeader)
+        };
+        // The numbers have at most 12 and 36 bits.
+        let count = usize::try_from(header(0..2)?).unwrap() + 1;
+        let index = usize::try_from(header(2..4)?).unwrap();
+        let crc32 = u32::try_from(header(4..10)?).map_err(|_| ReassembleError::InvalidHeader)?;
+        if index >= count {
+            return Err(ReassembleError::InvalidHeader);
+        }
+
+        match self.crc32 {
+            Some(expected) if expected != crc32 || self.parts.len() != count => {
+                return Err(ReassembleError::Mismatch);
+            }
+            Some(_) => {}
+            None => {
+                self.crc32 = Some(crc32);
+                self.parts = vec![None; count];
+            }
+        }
+
+        // The header is ASCII.
+        self.parts[index] = Some(part[HEADER_LEN..].to_string());
+
+        Ok(())
+    }
+
+    /// The number of parts of the payload, if any part was added.
+    #[inline]
+    pub fn count(&self) -> Option<usize> {
+        self.crc32.map(|_| self.parts.len())
+    }
+
+    /// The indexes of the parts that have not been added.
+    ///
+    /// This is empty until a part is added, as the number of parts is not known.
+    pub fn missing(&self) -> Vec<usize> {
+        self.parts
+            .iter()
+            .enumerate()
+            .filter(|(_, part)| part.is_none())
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Whether every part was added.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.crc32.is_some() && self.parts.iter().all(Option::is_some)
+    }
+
+    /// Reassemble the payload.
+    ///
+    /// # Errors
+    /// Returns an error if there are missing parts,
+    /// or if the payload does not match its checksum.
+    /// If no parts were added, only the first part is reported as missing.
+    pub fn finish(&self) -> Result<String, ReassembleError> {
+        let crc32 = self
+            .crc32
+            .ok_or_else(|| ReassembleError::Missing(vec![0]))?;
+
+        let missing = self.missing();
+        if !missing.is_empty() {
+            return Err(ReassembleError::Missing(missing));
+        }
+
+        let payload: String = self.parts.iter().flatten().map(String::as_str).collect();
+        if checksum(&payload) != crc32 {
+            return Err(ReassembleError::ChecksumMismatch);
+        }
+
+        Ok(payload)
+    }
+}
@@ -0,0 +1,393 @@
+use crate::compress::BitWriter;
+use crate::compress::HashMap;
+use crate::compress::HashSet;
+use crate::constants::CLOSE_CODE;
+use crate::constants::NUM_BASE_CODES;
+use crate::constants::U16_CODE;
+use crate::constants::U8_CODE;
+use crate::dictionary::LinkedDictionary;
+use crate::encoding::Encoding;
+use crate::token::CodeState;
+use crate::token::Token;
+use crate::token::TokenError;
+use crate::IntoWideIter;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// A compressor that takes its input in pieces.
+///
+/// The output is identical to compressing all of the input at once with the function for the encoding,
+/// including its padding.
+/// Only the dictionary is kept in memory, not the input,
+/// and compressed chars are output as soon as they are complete.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::{Compressor, Encoding};
+/// let mut compressor = Compressor::new(Encoding::Base64);
+/// let mut compressed = Vec::new();
+/// compressor.write("The quick brown fox ", &mut compressed);
+/// compressor.write("jumps over the lazy dog", &mut compressed);
+/// compressor.finish(&mut compressed);
+///
+/// let expected = lz_str::compress_to_base64("The quick brown fox jumps over the lazy dog");
+/// assert_eq!(String::from_utf16(&compressed).unwrap(), expected);
+/// ```
+#[derive(Debug)]
+pub struct Compressor {
+    encoding: Encoding,
+
+    /// The code of each char that was seen.
+    chars: HashMap<u16, u32>,
+
+    /// The chars that were seen, but were not output as a literal yet.
+    chars_to_create: HashSet<u16>,
+
+    /// The code of each entry of more than one char,
+    /// by the code of the entry without its last char and its last char.
+    entries: HashMap<(u32, u16), u32>,
+
+    /// The code of the next entry.
+    next_code: u32,
+
+    /// The code of the current word, w, along with its char if it only has one.
+    w: Option<(u32, Option<u16>)>,
+
+    state: CodeState,
+    writer: BitWriter<fn(u16) -> u16>,
+
+    /// The number of chars that were output.
+    len: usize,
+}
+
+impl Compressor {
+    /// Make a new [`Compressor`].
+    pub fn new(encoding: Encoding) -> Self {
+        Compressor {
+            encoding,
+
+            chars: HashMap::default(),
+            chars_to_create: HashSet::default(),
+            entries: HashMap::default(),
+            next_code: NUM_BASE_CODES.into(),
+            w: None,
+
+            state: CodeState::new(),
+            // The values are mapped to chars when they are taken, so they can be counted.
+            writer: BitWriter::new(0, encoding.bits_per_char(), core::convert::identity),
+
+            len: 0,
+        }
+    }
+
+    /// Compress more data, appending the compressed chars that are complete to `output`.
+    pub fn write(&mut self, data: impl IntoWideIter, output: &mut Vec<u16>) {
+        for c in data.into_wide_iter() {
+            self.write_u16(c);
+        }
+
+        self.take_output(output);
+    }
+
+    /// Finish the stream, appending the rest of the compressed chars and the padding to `output`.
+    pub fn finish(mut self, output: &mut Vec<u16>) {
+        self.finish_mut(output);
+    }
+
+    /// Finish the stream and start a new one, keeping the allocations of the dictionary.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn finish_and_reset(&mut self, output: &mut Vec<u16>) {
+        self.finish_mut(output);
+
+        self.chars.clear();
+        self.chars_to_create.clear();
+        self.entries.clear();
+        self.next_code = NUM_BASE_CODES.into();
+        self.w = None;
+
+        self.state = CodeState::new();
+        self.writer = BitWriter::new(0, self.encoding.bits_per_char(), core::convert::identity);
+
+        self.len = 0;
+    }
+
+    fn finish_mut(&mut self, output: &mut Vec<u16>) {
+        if let Some((w, w_char)) = self.w.take() {
+            self.produce_w(w, w_char);
+        }
+        self.write_token(Token::Close);
+
+        self.writer.pad_last_char();
+        self.take_output(output);
+
+        let padded_len = self.encoding.padded_len(self.len);
+        output.extend((self.len..padded_len).map(|_| self.encoding.padding_char()));
+    }
+
+    #[inline]
+    fn new_code(&mut self) -> u32 {
+        let code = self.next_code;
+        self.next_code += 1;
+        code
+    }
+
+    fn write_u16(&mut self, c: u16) {
+        let c_code = match self.chars.get(&c) {
+            Some(&code) => code,
+            None => {
+                let code = self.new_code();
+                self.chars.insert(c, code);
+                self.chars_to_create.insert(c);
+                code
+            }
+        };
+
+        self.w = Some(match self.w {
+            None => (c_code, Some(c)),
+            Some((w, w_char)) => match self.entries.get(&(w, c)) {
+                // w = wc.
+                Some(&wc) => (wc, None),
+                None => {
+                    let wc = self.new_code();
+                    self.entries.insert((w, c), wc);
+                    self.produce_w(w, w_char);
+
+                    // w = c.
+                    (c_code, Some(c))
+                }
+            },
+        });
+    }
+
+    /// Output w, as a literal if it is a char that was not output yet.
+    fn produce_w(&mut self, w: u32, w_char: Option<u16>) {
+        let token = match w_char {
+            Some(c) if self.chars_to_create.remove(&c) => match u8::try_from(c) {
+                Ok(c) => Token::U8(c),
+                Err(_) => Token::U16(c),
+            },
+            _ => Token::Reference(w),
+        };
+        self.write_token(token);
+    }
+
+    fn write_token(&mut self, token: Token) {
+        let num_bits = self.state.num_bits();
+        let result = self.state.push(token);
+        debug_assert!(result.is_ok());
+
+        match token {
+            Token::U8(c) => {
+                self.writer.write_bits(num_bits, U8_CODE.into());
+                self.writer.write_bits(8, c.into());
+            }
+            Token::U16(c) => {
+                self.writer.write_bits(num_bits, U16_CODE.into());
+                self.writer.write_bits(16, c.into());
+            }
+            Token::Reference(code) => self.writer.write_bits(num_bits, code),
+            Token::Close => self.writer.write_bits(num_bits, CLOSE_CODE.into()),
+        }
+    }
+
+    /// Move the complete values to `output` as chars of the encoding.
+    fn take_output(&mut self, output: &mut Vec<u16>) {
+        let start = output.len();
+        self.writer.take_output(output);
+        for c in output[start..].iter_mut() {
+            *c = self.encoding.value_to_char(*c);
+        }
+        self.len += output.len() - start;
+    }
+}
+
+/// A decompressor that takes its input in pieces.
+///
+/// Decompressed data is output as soon as the code for it is complete.
+/// Anything after the end of the stream is ignored, like the reference implementation.
+/// The dictionary is kept in memory, with a constant size per entry.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::{Decompressor, Encoding};
+/// let compressed = lz_str::compress_to_encoded_uri_component("The quick brown fox");
+///
+/// let mut decompressor = Decompressor::new(Encoding::EncodedUriComponent);
+/// let mut decompressed = Vec::new();
+/// for part in compressed.as_bytes().chunks(3) {
+///     let part = std::str::from_utf8(part).unwrap();
+///     decompressor.write(part, &mut decompressed).expect("invalid data");
+/// }
+/// assert!(decompressor.is_done());
+/// decompressor.finish().expect("invalid data");
+///
+/// assert_eq!(String::from_utf16(&decompressed).unwrap(), "The quick brown fox");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Decompressor {
+    encoding: Encoding,
+
+    /// The values that were not fully read yet.
+    values: VecDeque<u16>,
+
+    /// The number of bits of the first value that were read.
+    bit_offset: usize,
+
+    state: CodeState,
+    dictionary: LinkedDictionary,
+
+    /// Whether any value was written.
+    started: bool,
+
+    /// The result, once the stream ended or failed.
+    result: Option<Result<(), TokenError>>,
+}
+
+impl Decompressor {
+    /// Make a new [`Decompressor`].
+    pub fn new(encoding: Encoding) -> Self {
+        Decompressor {
+            encoding,
+
+            values: VecDeque::new(),
+            bit_offset: 0,
+
+            state: CodeState::new(),
+            dictionary: LinkedDictionary::new(),
+
+            started: false,
+            result: None,
+        }
+    }
+
+    /// Decompress more data, appending the decompressed data to `output`.
+    ///
+    /// # Errors
+    /// Returns an error if the compressed data is invalid.
+    /// Once an error is returned, every later call returns it as well.
+    pub fn write(
+        &mut self,
+        compressed: impl IntoWideIter,
+        output: &mut Vec<u16>,
+    ) -> Result<(), TokenError> {
+        for c in compressed.into_wide_iter() {
+            if self.result.is_some() {
+                break;
+            }
+
+            if let Some(value) = self.encoding.char_to_value(c) {
+                self.started = true;
+                self.values.push_back(value);
+                self.decode(output);
+            }
+        }
+
+        match self.result {
+            Some(Err(error)) => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the end of the stream was reached.
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.result == Some(Ok(()))
+    }
+
+    /// Finish the stream.
+    ///
+    /// # Errors
+    /// Returns an error if the compressed data is invalid,
+    /// or if it ended before the end of the stream.
+    /// Like the reference implementation, empty compressed data is valid.
+    pub fn finish(self) -> Result<(), TokenError> {
+        match self.result {
+            Some(result) => result,
+            None if !self.started => Ok(()),
+            None => Err(TokenError::UnexpectedEnd),
+        }
+    }
+
+    /// The number of bits that can be read.
+    #[inline]
+    fn available_bits(&self) -> usize {
+        self.values.len() * usize::from(self.encoding.bits_per_char()) - self.bit_offset
+    }
+
+    /// Read `n` bits after skipping `skip` bits, without consuming them.
+    fn peek_bits(&self, skip: usize, n: u8) -> u32 {
+        let bits_per_char = usize::from(self.encoding.bits_per_char());
+
+        let mut result = 0;
+        for i in 0..n {
+            let position = self.bit_offset + skip + usize::from(i);
+            let value = self.values[position / bits_per_char];
+            let bit = (value >> (bits_per_char - 1 - position % bits_per_char)) & 1;
+            result |= u32::from(bit) << i;
+        }
+
+        result
+    }
+
+    /// Consume `n` bits.
+    fn consume(&mut self, n: usize) {
+        let bits_per_char = usize::from(self.encoding.bits_per_char());
+
+        let position = self.bit_offset + n;
+        self.values.drain(..position / bits_per_char);
+        self.bit_offset = position % bits_per_char;
+    }
+
+    /// Decode as many complete tokens as possible.
+    fn decode(&mut self, output: &mut Vec<u16>) {
+        while self.result.is_none() {
+            let num_bits = self.state.num_bits();
+            let available = self.available_bits();
+            if available < usize::from(num_bits) {
+                return;
+            }
+
+            let code = self.peek_bits(0, num_bits);
+            let (token, len) = match u8::try_from(code) {
+                Ok(U8_CODE) | Ok(U16_CODE) => {
+                    let char_bits = if code == u32::from(U8_CODE) { 8 } else { 16 };
+                    if available < usize::from(num_bits + char_bits) {
+                        return;
+                    }
+
+                    // 8 bits always fit in a u8, and 16 bits in a u16.
+                    let c = self.peek_bits(num_bits.into(), char_bits);
+                    let token = if char_bits == 8 {
+                        Token::U8(u8::try_from(c).unwrap())
+                    } else {
+                        Token::U16(u16::try_from(c).unwrap())
+                    };
+                    (token, num_bits + char_bits)
+                }
+                Ok(CLOSE_CODE) => (Token::Close, num_bits),
+                _ => (Token::Reference(code), num_bits),
+            };
+            self.consume(len.into());
+
+            if let Err(error) = self.state.push(token) {
+                self.result = Some(Err(error));
+                return;
+            }
+
+            if token == Token::Close {
+                self.result = Some(Ok(()));
+                return;
+            }
+
+            match self.dictionary.push(token) {
+                Some(code) => {
+                    // The code was just pushed.
+                    let len = self.dictionary.get(code).unwrap().len;
+                    self.dictionary.extend_from_entry(code, 0, len, output);
+                }
+                None => self.result = Some(Err(TokenError::InvalidReference(code))),
+            }
+        }
+    }
+}
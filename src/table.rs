@@ -0,0 +1,248 @@
+use crate::compress::compress_to_base64;
+use crate::compress::HashMap;
+use crate::decompress::decompress_from_base64;
+use crate::IntoWideIter;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// The first line of a string table.
+const MAGIC: &str = "LZST1";
+
+/// The default number of `u16`s in a block, before compression.
+const DEFAULT_BLOCK_LEN: usize = 4096;
+
+/// A builder for a string table.
+///
+/// A string table stores many named strings,
+/// packing them into blocks that are compressed together to get a better ratio than compressing each alone.
+/// An index allows looking up a single string by only decompressing its block.
+///
+/// A table is a string of lines separated by '\n':
+/// 1. "LZST1"
+/// 2. The index, compressed with [`crate::compress_to_base64`]
+/// 3. Each block, compressed with [`crate::compress_to_base64`]
+///
+/// The index has an entry for each string,
+/// made of its block number, start and length in the block in `u16`s, and the length of its key, separated by ','.
+/// The key follows right after the last ',', with no separator before the next entry.
+/// Each block is its strings one after another.
+/// This makes it simple to read the table with the reference implementation as well.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::{StringTable, StringTableBuilder};
+/// let mut builder = StringTableBuilder::new();
+/// builder.insert("greeting", "Hello, world!");
+/// builder.insert("farewell", "Goodbye, world!");
+/// let table = builder.build();
+///
+/// let table = StringTable::new(&table).expect("invalid table");
+/// let greeting: Vec<u16> = "Hello, world!".encode_utf16().collect();
+/// assert_eq!(table.get("greeting"), Some(greeting));
+/// assert_eq!(table.get("missing"), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StringTableBuilder {
+    entries: Vec<(String, Vec<u16>)>,
+
+    /// The index of each key in `entries`.
+    keys: HashMap<String, usize>,
+
+    block_len: usize,
+}
+
+impl StringTableBuilder {
+    /// Make a new [`StringTableBuilder`].
+    pub fn new() -> Self {
+        StringTableBuilder {
+            entries: Vec::new(),
+            keys: HashMap::default(),
+            block_len: DEFAULT_BLOCK_LEN,
+        }
+    }
+
+    /// Set the number of `u16`s in a block, before compression.
+    ///
+    /// Blocks are filled with strings until they reach this length,
+    /// so larger blocks compress better but make each lookup slower.
+    /// Defaults to 4096.
+    pub fn block_len(&mut self, block_len: usize) -> &mut Self {
+        self.block_len = block_len;
+        self
+    }
+
+    /// Add a string to the table, replacing any string with the same key.
+    pub fn insert(&mut self, key: &str, value: impl IntoWideIter) -> &mut Self {
+        let value: Vec<u16> = value.into_wide_iter().collect();
+        match self.keys.get(key) {
+            Some(&i) => self.entries[i].1 = value,
+            None => {
+                self.keys.insert(key.to_string(), self.entries.len());
+                self.entries.push((key.to_string(), value));
+            }
+        }
+        self
+    }
+
+    /// Build the table.
+    ///
+    /// Strings are packed into blocks in the order they were first inserted.
+    pub fn build(&self) -> String {
+        let mut index: Vec<u16> = Vec::new();
+        let mut blocks: Vec<Vec<u16>> = Vec::new();
+        let mut block: Vec<u16> = Vec::new();
+        let mut block_entries = 0;
+        for (key, value) in self.entries.iter() {
+            if block_entries != 0 && block.len() + value.len() > self.block_len {
+                blocks.push(core::mem::take(&mut block));
+                block_entries = 0;
+            }
+
+            let key: Vec<u16> = key.encode_utf16().collect();
+            let entry = format!(
+                "{},{},{},{},",
+                blocks.len(),
+                block.len(),
+                value.len(),
+                key.len()
+            );
+            index.extend(entry.encode_utf16());
+            index.extend(key);
+
+            block.extend(value);
+            block_entries += 1;
+        }
+        if block_entries != 0 {
+            blocks.push(block);
+        }
+
+        let mut table = String::from(MAGIC);
+        table.push('\n');
+        table.push_str(&compress_to_base64(&index));
+        for block in blocks.iter() {
+            table.push('\n');
+            table.push_str(&compress_to_base64(block));
+        }
+
+        table
+    }
+}
+
+impl Default for StringTableBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The location of a string in a table.
+#[derive(Debug, Clone, Copy)]
+struct Location {
+    block: usize,
+    start: usize,
+    len: usize,
+}
+
+/// A reader for a string table from [`StringTableBuilder`].
+///
+/// Creating a reader only decompresses the index.
+/// Each lookup decompresses the block of the string.
+#[derive(Debug, Clone)]
+pub struct StringTable<'a> {
+    blocks: Vec<&'a str>,
+    locations: HashMap<String, Location>,
+}
+
+impl<'a> StringTable<'a> {
+    /// Read the index of a string table.
+    ///
+    /// # Errors
+    /// Returns `None` if the table or its index is invalid.
+    pub fn new(table: &'a str) -> Option<Self> {
+        let mut lines = table.split('\n');
+        if lines.next()? != MAGIC {
+            return None;
+        }
+
+        let index = decompress_from_base64(lines.next()?)?;
+        let blocks: Vec<&str> = lines.collect();
+
+        let mut locations = HashMap::default();
+        let mut rest = &index[..];
+        while !rest.is_empty() {
+            let mut fields = [0; 4];
+            for field in fields.iter_mut() {
+                let end = rest.iter().position(|&c| c == u16::from(b','))?;
+                let digits = String::from_utf16(&rest[..end]).ok()?;
+                *field = digits.parse::<usize>().ok()?;
+                rest = &rest[end + 1..];
+            }
+            let [block, start, len, key_len] = fields;
+            if block >= blocks.len() {
+                return None;
+            }
+
+            let key = String::from_utf16(rest.get(..key_len)?).ok()?;
+            rest = &rest[key_len..];
+
+            locations.insert(key, Location { block, start, len });
+        }
+
+        Some(StringTable { blocks, locations })
+    }
+
+    /// The number of strings in the table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    /// Whether the table has no strings.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    /// Whether the table has a string with the given key.
+    #[inline]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.locations.contains_key(key)
+    }
+
+    /// Iterate over the keys of the table, in no particular order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.locations.keys().map(String::as_str)
+    }
+
+    /// Get a string, decompressing only its block.
+    ///
+    /// Returns `None` if the key is not in the table, or if its block is invalid.
+    pub fn get(&self, key: &str) -> Option<Vec<u16>> {
+        let location = self.locations.get(key)?;
+        let block = decompress_from_base64(self.blocks[location.block])?;
+
+        let end = location.start.checked_add(location.len)?;
+        block.get(location.start..end).map(<[u16]>::to_vec)
+    }
+
+    /// Get several strings at once, decompressing each block at most once.
+    ///
+    /// Each string is `None` if its key is not in the table, or if its block is invalid.
+    pub fn get_all(&self, keys: &[&str]) -> Vec<Option<Vec<u16>>> {
+        let mut blocks: HashMap<usize, Option<Vec<u16>>> = HashMap::default();
+
+        keys.iter()
+            .map(|&key| {
+                let location = self.locations.get(key)?;
+                let block = blocks
+                    .entry(location.block)
+                    .or_insert_with(|| decompress_from_base64(self.blocks[location.block]))
+                    .as_ref()?;
+
+                let end = location.start.checked_add(location.len)?;
+                block.get(location.start..end).map(<[u16]>::to_vec)
+            })
+            .collect()
+    }
+}
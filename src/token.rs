@@ -0,0 +1,315 @@
+#[cfg(feature = "alloc")]
+use crate::compress::BitWriter;
+use crate::constants::CLOSE_CODE;
+use crate::constants::NUM_BASE_CODES;
+use crate::constants::START_CODE_BITS;
+use crate::constants::U16_CODE;
+use crate::constants::U8_CODE;
+use crate::decompress::DecompressContext;
+use crate::IntoWideIter;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// A single code of an lz-string stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Token {
+    /// A char that fits in 8 bits.
+    ///
+    /// This adds a new single char entry to the dictionary.
+    U8(u8),
+
+    /// A char that needs 16 bits.
+    ///
+    /// This adds a new single char entry to the dictionary.
+    U16(u16),
+
+    /// A reference to a dictionary entry.
+    ///
+    /// This may also refer to the entry that is about to be created,
+    /// which is the previous entry followed by its own first char.
+    Reference(u32),
+
+    /// The end of the stream.
+    Close,
+}
+
+/// An error that occurs while reading or writing a token stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenError {
+    /// The stream ended before a [`Token::Close`].
+    UnexpectedEnd,
+
+    /// A [`Token::Reference`] pointed to an entry that does not exist.
+    InvalidReference(u32),
+}
+
+impl core::fmt::Display for TokenError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "the stream ended before the close code"),
+            Self::InvalidReference(code) => {
+                write!(f, "the code {code} does not refer to a dictionary entry")
+            }
+        }
+    }
+}
+
+impl core::error::Error for TokenError {}
+
+/// The bookkeeping shared by everything that reads or writes codes.
+///
+/// This tracks the size of the dictionary and the current code width,
+/// without storing any dictionary entries.
+#[derive(Debug, Clone)]
+pub(crate) struct CodeState {
+    /// The current number of bits in a code.
+    num_bits: u8,
+
+    // The counter for increasing the current number of bits in a code.
+    // The max size of this is 1 << max(num_bits) == 1 + u32::MAX, so we use u64.
+    enlarge_in: u64,
+
+    /// The number of entries in the dictionary, including the base codes.
+    dictionary_len: u32,
+
+    /// Whether a char or reference was already processed.
+    started: bool,
+}
+
+impl CodeState {
+    /// Make a new [`CodeState`] for the start of a stream.
+    #[inline]
+    pub fn new() -> Self {
+        CodeState {
+            num_bits: START_CODE_BITS,
+            enlarge_in: 2,
+            dictionary_len: NUM_BASE_CODES.into(),
+            started: false,
+        }
+    }
+
+    /// The width of the next code.
+    #[inline]
+    pub fn num_bits(&self) -> u8 {
+        self.num_bits
+    }
+
+    /// The number of entries in the dictionary, including the base codes.
+    #[inline]
+    pub fn dictionary_len(&self) -> u32 {
+        self.dictionary_len
+    }
+
+    /// The number of codes until the code width grows.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn enlarge_in(&self) -> u64 {
+        self.enlarge_in
+    }
+
+    #[inline]
+    fn decrement_enlarge_in(&mut self) {
+        self.enlarge_in -= 1;
+        if self.enlarge_in == 0 {
+            self.enlarge_in = 1 << self.num_bits;
+            self.num_bits += 1;
+        }
+    }
+
+    /// Validate a token and account for the entries it creates.
+    #[inline]
+    pub fn push(&mut self, token: Token) -> Result<(), TokenError> {
+        match token {
+            Token::U8(_) | Token::U16(_) => {
+                self.dictionary_len += 1;
+                self.decrement_enlarge_in();
+            }
+            Token::Reference(code) => {
+                // The first entry cannot be a reference,
+                // and the only entry that may be referenced before it exists is the next one.
+                if !self.started || code < u32::from(NUM_BASE_CODES) || code > self.dictionary_len {
+                    return Err(TokenError::InvalidReference(code));
+                }
+            }
+            Token::Close => return Ok(()),
+        }
+
+        // Every entry after the first adds the previous entry plus its first char.
+        if self.started {
+            self.dictionary_len += 1;
+        }
+        self.started = true;
+        self.decrement_enlarge_in();
+
+        Ok(())
+    }
+}
+
+/// An iterator over the [`Token`]s of a compressed stream.
+///
+/// This is created by [`tokenize`].
+/// It stops after yielding a [`Token::Close`] or an error.
+#[derive(Debug)]
+pub struct Tokenizer<I> {
+    ctx: Option<DecompressContext<I>>,
+    state: CodeState,
+    done: bool,
+}
+
+impl<I> Tokenizer<I>
+where
+    I: Iterator<Item = u16>,
+{
+    /// Make a new [`Tokenizer`].
+    ///
+    /// # Panics
+    /// Panics if `bits_per_char` is greater than the number of bits in a `u16`.
+    #[inline]
+    pub(crate) fn new(compressed: I, bits_per_char: u8) -> Self {
+        Tokenizer {
+            ctx: DecompressContext::new(compressed, bits_per_char),
+            state: CodeState::new(),
+            done: false,
+        }
+    }
+
+    /// The width, in bits, of the next code.
+    #[inline]
+    pub fn num_bits(&self) -> u8 {
+        self.state.num_bits()
+    }
+
+    /// The number of entries in the dictionary, including the 3 base codes.
+    #[inline]
+    pub fn dictionary_len(&self) -> u32 {
+        self.state.dictionary_len()
+    }
+
+    /// The number of bits that were read from the compressed data.
+    #[inline]
+    pub fn bit_position(&self) -> usize {
+        self.ctx.as_ref().map_or(0, |ctx| ctx.bit_position())
+    }
+
+    /// The code bookkeeping before the next token.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub(crate) fn state(&self) -> &CodeState {
+        &self.state
+    }
+
+    #[inline]
+    fn read_token(ctx: &mut DecompressContext<I>, num_bits: u8) -> Option<Token> {
+        let code = ctx.read_bits(num_bits)?;
+        let token = match u8::try_from(code) {
+            // 8 bits always fit in a u8.
+            Ok(U8_CODE) => Token::U8(u8::try_from(ctx.read_bits(8)?).unwrap()),
+            // 16 bits always fit in a u16.
+            Ok(U16_CODE) => Token::U16(u16::try_from(ctx.read_bits(16)?).unwrap()),
+            Ok(CLOSE_CODE) => Token::Close,
+            _ => Token::Reference(code),
+        };
+
+        Some(token)
+    }
+}
+
+impl<I> Iterator for Tokenizer<I>
+where
+    I: Iterator<Item = u16>,
+{
+    type Item = Result<Token, TokenError>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        // An empty input has no tokens at all.
+        let ctx = self.ctx.as_mut()?;
+
+        let result = Self::read_token(ctx, self.state.num_bits())
+            .ok_or(TokenError::UnexpectedEnd)
+            .and_then(|token| self.state.push(token).map(|_| token));
+
+        self.done = matches!(result, Ok(Token::Close) | Err(_));
+
+        Some(result)
+    }
+}
+
+impl<I> core::iter::FusedIterator for Tokenizer<I> where I: Iterator<Item = u16> {}
+
+/// Read the [`Token`]s of a compressed stream.
+///
+/// `bits_per_char` must match the value used to compress the data,
+/// which is 16 for [`crate::compress`].
+///
+/// # Panics
+/// Panics if `bits_per_char` is greater than the number of bits in a `u16`.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::Token;
+/// let compressed = lz_str::compress("abab");
+/// let tokens: Result<Vec<Token>, _> = lz_str::tokenize(&compressed, 16).collect();
+/// assert_eq!(
+///     tokens.unwrap(),
+///     [Token::U8(b'a'), Token::U8(b'b'), Token::Reference(5), Token::Close]
+/// );
+/// ```
+#[inline]
+pub fn tokenize<I>(compressed: I, bits_per_char: u8) -> Tokenizer<I::Iter>
+where
+    I: IntoWideIter,
+{
+    Tokenizer::new(compressed.into_wide_iter(), bits_per_char)
+}
+
+/// Write [`Token`]s as a compressed stream.
+///
+/// Writing stops after the first [`Token::Close`].
+/// If the tokens do not contain one, it is added.
+/// The arguments are the same as [`crate::compress_internal`].
+///
+/// # Errors
+/// Returns an error if a token references a dictionary entry that would not exist when decoding.
+///
+/// # Panics
+/// Panics if `bits_per_char` is greater than the number of bits in a `u16`.
+#[cfg(feature = "alloc")]
+pub fn encode_tokens<I, F>(tokens: I, bits_per_char: u8, to_char: F) -> Result<Vec<u16>, TokenError>
+where
+    I: IntoIterator<Item = Token>,
+    F: Fn(u16) -> u16,
+{
+    let tokens = tokens.into_iter();
+    let mut writer = BitWriter::new(tokens.size_hint().0, bits_per_char, to_char);
+    let mut state = CodeState::new();
+
+    for token in tokens {
+        let num_bits = state.num_bits();
+        state.push(token)?;
+
+        match token {
+            Token::U8(c) => {
+                writer.write_bits(num_bits, U8_CODE.into());
+                writer.write_bits(8, c.into());
+            }
+            Token::U16(c) => {
+                writer.write_bits(num_bits, U16_CODE.into());
+                writer.write_bits(16, c.into());
+            }
+            Token::Reference(code) => {
+                writer.write_bits(num_bits, code);
+            }
+            Token::Close => break,
+        }
+    }
+
+    writer.write_bits(state.num_bits(), CLOSE_CODE.into());
+
+    Ok(writer.finish())
+}
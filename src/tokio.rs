@@ -0,0 +1,293 @@
+//! [`AsyncRead`] and [`AsyncWrite`] adapters for tokio.
+//!
+//! The compressed side of each adapter holds the chars of an [`Encoding`] as bytes.
+//! They are stored as UTF-8, except for [`Encoding::Raw`],
+//! which is stored like the output of [`crate::compress_to_uint8_array`].
+//! The uncompressed side holds UTF-8 text.
+//!
+//! Data is compressed and decompressed as it passes through,
+//! so whole payloads never need to be buffered.
+//!
+//! # Example
+//! ```rust
+//! # use lz_str::Encoding;
+//! # use lz_str::tokio::{CompressWriter, DecompressReader};
+//! # use tokio::io::{AsyncReadExt, AsyncWriteExt};
+//! # futures::executor::block_on(async {
+//! let mut writer = CompressWriter::new(Vec::new(), Encoding::Base64);
+//! writer.write_all(b"The quick brown fox").await?;
+//! writer.shutdown().await?;
+//!
+//! let compressed = writer.into_inner();
+//! assert_eq!(compressed, lz_str::compress_to_base64("The quick brown fox").as_bytes());
+//!
+//! let mut reader = DecompressReader::new(&compressed[..], Encoding::Base64);
+//! let mut decompressed = String::new();
+//! reader.read_to_string(&mut decompressed).await?;
+//! assert_eq!(decompressed, "The quick brown fox");
+//! # std::io::Result::Ok(())
+//! # }).unwrap();
+//! ```
+
+use crate::encoding::Encoding;
+use crate::io::ByteCompressor;
+use crate::io::ByteDecompressor;
+use crate::io::ReadState;
+use crate::io::Transform;
+use crate::io::WriteState;
+use ::tokio::io::AsyncRead;
+use ::tokio::io::AsyncWrite;
+use ::tokio::io::ReadBuf;
+use std::io;
+use std::pin::Pin;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+/// Read from `reader` through `state`.
+fn poll_read<R, T>(
+    state: &mut ReadState<T>,
+    reader: &mut R,
+    cx: &mut Context<'_>,
+    buf: &mut ReadBuf<'_>,
+) -> Poll<io::Result<()>>
+where
+    R: AsyncRead + Unpin,
+    T: Transform,
+{
+    let len = ready!(state.poll_read(buf.initialize_unfilled(), |input| {
+        let mut input = ReadBuf::new(input);
+        ready!(Pin::new(&mut *reader).poll_read(cx, &mut input))?;
+        Poll::Ready(Ok(input.filled().len()))
+    }))?;
+    buf.advance(len);
+
+    Poll::Ready(Ok(()))
+}
+
+/// Write `buf` to `writer` through `state`.
+fn poll_write<W, T>(
+    state: &mut WriteState<T>,
+    writer: &mut W,
+    cx: &mut Context<'_>,
+    buf: &[u8],
+) -> Poll<io::Result<usize>>
+where
+    W: AsyncWrite + Unpin,
+    T: Transform,
+{
+    state.poll_write(buf, |output| Pin::new(&mut *writer).poll_write(cx, output))
+}
+
+/// Write the pending output of `state` to `writer`, then flush it.
+fn poll_flush<W, T>(
+    state: &mut WriteState<T>,
+    writer: &mut W,
+    cx: &mut Context<'_>,
+) -> Poll<io::Result<()>>
+where
+    W: AsyncWrite + Unpin,
+    T: Transform,
+{
+    ready!(state.poll_write_output(|output| Pin::new(&mut *writer).poll_write(cx, output)))?;
+    Pin::new(writer).poll_flush(cx)
+}
+
+/// Finish `state`, write the rest of its output to `writer`, then shut it down.
+fn poll_shutdown<W, T>(
+    state: &mut WriteState<T>,
+    writer: &mut W,
+    cx: &mut Context<'_>,
+) -> Poll<io::Result<()>>
+where
+    W: AsyncWrite + Unpin,
+    T: Transform,
+{
+    ready!(state.poll_finish(|output| Pin::new(&mut *writer).poll_write(cx, output)))?;
+    Pin::new(writer).poll_shutdown(cx)
+}
+
+macro_rules! impl_inner {
+    ($name:ident) => {
+        impl<T> $name<T> {
+            /// Get a reference to the inner stream.
+            pub fn get_ref(&self) -> &T {
+                &self.inner
+            }
+
+            /// Get a mutable reference to the inner stream.
+            ///
+            /// Reading from or writing to it directly will corrupt the stream.
+            pub fn get_mut(&mut self) -> &mut T {
+                &mut self.inner
+            }
+
+            /// Get the inner stream back.
+            pub fn into_inner(self) -> T {
+                self.inner
+            }
+        }
+    };
+}
+
+/// A reader that compresses UTF-8 text read from another reader.
+#[derive(Debug)]
+pub struct CompressReader<R> {
+    inner: R,
+    state: ReadState<ByteCompressor>,
+}
+
+impl<R> CompressReader<R> {
+    /// Make a new [`CompressReader`] that compresses the text of `inner` into `encoding`.
+    pub fn new(inner: R, encoding: Encoding) -> Self {
+        CompressReader {
+            inner,
+            state: ReadState::new(ByteCompressor::new(encoding)),
+        }
+    }
+}
+
+impl_inner!(CompressReader);
+
+impl<R> AsyncRead for CompressReader<R>
+where
+    R: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        poll_read(&mut this.state, &mut this.inner, cx, buf)
+    }
+}
+
+/// A reader that decompresses the compressed data read from another reader into UTF-8 text.
+///
+/// Reading fails with [`io::ErrorKind::InvalidData`] if the data is invalid,
+/// or if the decompressed data is not valid UTF-16.
+#[derive(Debug)]
+pub struct DecompressReader<R> {
+    inner: R,
+    state: ReadState<ByteDecompressor>,
+}
+
+impl<R> DecompressReader<R> {
+    /// Make a new [`DecompressReader`] that decompresses the data of `inner` from `encoding`.
+    pub fn new(inner: R, encoding: Encoding) -> Self {
+        DecompressReader {
+            inner,
+            state: ReadState::new(ByteDecompressor::new(encoding)),
+        }
+    }
+}
+
+impl_inner!(DecompressReader);
+
+impl<R> AsyncRead for DecompressReader<R>
+where
+    R: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        poll_read(&mut this.state, &mut this.inner, cx, buf)
+    }
+}
+
+/// A writer that compresses UTF-8 text before writing it to another writer.
+///
+/// The stream is only finished by [`AsyncWrite::poll_shutdown`],
+/// so the writer must be shut down to write the end of the compressed data.
+#[derive(Debug)]
+pub struct CompressWriter<W> {
+    inner: W,
+    state: WriteState<ByteCompressor>,
+}
+
+impl<W> CompressWriter<W> {
+    /// Make a new [`CompressWriter`] that writes text to `inner` compressed into `encoding`.
+    pub fn new(inner: W, encoding: Encoding) -> Self {
+        CompressWriter {
+            inner,
+            state: WriteState::new(ByteCompressor::new(encoding)),
+        }
+    }
+}
+
+impl_inner!(CompressWriter);
+
+impl<W> AsyncWrite for CompressWriter<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        poll_write(&mut this.state, &mut this.inner, cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        poll_flush(&mut this.state, &mut this.inner, cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        poll_shutdown(&mut this.state, &mut this.inner, cx)
+    }
+}
+
+/// A writer that decompresses compressed data before writing it to another writer as UTF-8 text.
+///
+/// Writing fails with [`io::ErrorKind::InvalidData`] if the data is invalid,
+/// or if the decompressed data is not valid UTF-16.
+/// [`AsyncWrite::poll_shutdown`] also fails if the data ended before the end of the stream.
+#[derive(Debug)]
+pub struct DecompressWriter<W> {
+    inner: W,
+    state: WriteState<ByteDecompressor>,
+}
+
+impl<W> DecompressWriter<W> {
+    /// Make a new [`DecompressWriter`] that writes the decompressed data of `encoding` to `inner`.
+    pub fn new(inner: W, encoding: Encoding) -> Self {
+        DecompressWriter {
+            inner,
+            state: WriteState::new(ByteDecompressor::new(encoding)),
+        }
+    }
+}
+
+impl_inner!(DecompressWriter);
+
+impl<W> AsyncWrite for DecompressWriter<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        poll_write(&mut this.state, &mut this.inner, cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        poll_flush(&mut this.state, &mut this.inner, cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        poll_shutdown(&mut this.state, &mut this.inner, cx)
+    }
+}
@@ -0,0 +1,204 @@
+use crate::compress::compress_internal;
+use crate::crc32::Crc32;
+use crate::decompress::decompress_tokens;
+use crate::encoding::Encoding;
+use crate::token::TokenError;
+use crate::token::Tokenizer;
+use crate::IntoWideIter;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// The first bits of a trailer, "LZ" in ASCII.
+const MAGIC: u16 = 0x4C5A;
+
+/// The number of bits in a trailer.
+const TRAILER_BITS: usize = 16 + 8 + 32 + 32;
+
+/// Metadata stored after the close code of a compressed stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Trailer {
+    /// An application defined version of the format of the data.
+    pub version: u8,
+
+    /// The length of the decompressed data in `u16`s, modulo 2^32.
+    pub len: u32,
+
+    /// The CRC-32 of the decompressed data, as little endian bytes.
+    pub crc32: u32,
+}
+
+impl Trailer {
+    /// Make the trailer for some data.
+    pub fn new(data: &[u16], version: u8) -> Self {
+        let mut crc32 = Crc32::new();
+        crc32.update_wide(data);
+
+        Trailer {
+            version,
+            // Masked to fit.
+            len: u32::try_from(data.len() & 0xFFFF_FFFF).unwrap(),
+            crc32: crc32.finish(),
+        }
+    }
+
+    /// The number of chars a trailer takes.
+    #[inline]
+    fn num_chars(bits_per_char: u8) -> usize {
+        let bits_per_char = usize::from(bits_per_char);
+        TRAILER_BITS.div_ceil(bits_per_char)
+    }
+
+    /// Write the trailer as values of the bit stream, most significant bits first.
+    fn to_values(self, bits_per_char: u8) -> Vec<u16> {
+        let num_chars = Self::num_chars(bits_per_char);
+        let bits = (u128::from(MAGIC) << 72)
+            | (u128::from(self.version) << 64)
+            | (u128::from(self.len) << 32)
+            | u128::from(self.crc32);
+        let bits = bits << (num_chars * usize::from(bits_per_char) - TRAILER_BITS);
+        let mask = (1_u128 << bits_per_char) - 1;
+
+        (0..num_chars)
+            .rev()
+            .map(|i| {
+                let value = (bits >> (i * usize::from(bits_per_char))) & mask;
+                // The mask keeps at most 16 bits.
+                u16::try_from(value).unwrap()
+            })
+            .collect()
+    }
+
+    /// Read a trailer from values of the bit stream.
+    ///
+    /// Returns `None` if the values do not start with a trailer.
+    fn from_values(values: &[u16], bits_per_char: u8) -> Option<Self> {
+        let values = values.get(..Self::num_chars(bits_per_char))?;
+
+        let mut bits: u128 = 0;
+        for &value in values {
+            if u32::from(value) >> bits_per_char != 0 {
+                return None;
+            }
+            bits = (bits << bits_per_char) | u128::from(value);
+        }
+        let bits = bits >> (values.len() * usize::from(bits_per_char) - TRAILER_BITS);
+
+        // The shifts leave exactly the bits of each field.
+        if u16::try_from(bits >> 72).unwrap() != MAGIC {
+            return None;
+        }
+
+        Some(Trailer {
+            version: u8::try_from((bits >> 64) & 0xFF).unwrap(),
+            len: u32::try_from((bits >> 32) & 0xFFFF_FFFF).unwrap(),
+            crc32: u32::try_from(bits & 0xFFFF_FFFF).unwrap(),
+        })
+    }
+}
+
+/// An error from [`decompress_with_trailer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrailerError {
+    /// The compressed data could not be decompressed.
+    InvalidData(TokenError),
+
+    /// The decompressed data does not match the trailer.
+    Mismatch(Trailer),
+}
+
+impl core::fmt::Display for TrailerError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidData(error) => write!(f, "invalid compressed data: {error}"),
+            Self::Mismatch(_) => write!(f, "the decompressed data does not match the trailer"),
+        }
+    }
+}
+
+impl core::error::Error for TrailerError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidData(error) => Some(error),
+            Self::Mismatch(_) => None,
+        }
+    }
+}
+
+/// Compress data into the chars of the given [`Encoding`], with a [`Trailer`] after the close code.
+///
+/// Decompressors stop at the close code,
+/// so the result still decompresses with the functions of this library and the reference implementation.
+/// The trailer uses the same alphabet and comes before the encoding's padding.
+///
+/// # Example
+/// ```rust
+/// # use lz_str::Encoding;
+/// let compressed = lz_str::compress_with_trailer("The quick brown fox", Encoding::Base64, 1);
+/// let compressed = String::from_utf16(&compressed).unwrap();
+///
+/// let expected: Vec<u16> = "The quick brown fox".encode_utf16().collect();
+/// assert_eq!(lz_str::decompress_from_base64(&compressed), Some(expected.clone()));
+///
+/// let (decompressed, trailer) = lz_str::decompress_with_trailer(&compressed, Encoding::Base64)
+///     .expect("invalid data");
+/// assert_eq!(decompressed, expected);
+/// assert_eq!(trailer.map(|trailer| trailer.version), Some(1));
+/// ```
+pub fn compress_with_trailer(data: impl IntoWideIter, encoding: Encoding, version: u8) -> Vec<u16> {
+    let data: Vec<u16> = data.into_wide_iter().collect();
+
+    let mut compressed = compress_internal(&data, encoding.bits_per_char(), |n| {
+        encoding.value_to_char(n)
+    });
+    compressed.extend(
+        Trailer::new(&data, version)
+            .to_values(encoding.bits_per_char())
+            .into_iter()
+            .map(|n| encoding.value_to_char(n)),
+    );
+    encoding.pad(&mut compressed);
+
+    compressed
+}
+
+/// Decompress the chars of the given [`Encoding`], reading and verifying the [`Trailer`] if there is one.
+///
+/// Returns `None` for the trailer if the stream does not have one.
+///
+/// # Errors
+/// Returns an error if the compressed data could not be decompressed,
+/// or if the decompressed data does not match the trailer.
+pub fn decompress_with_trailer(
+    compressed: impl IntoWideIter,
+    encoding: Encoding,
+) -> Result<(Vec<u16>, Option<Trailer>), TrailerError> {
+    let values: Vec<u16> = encoding.values(compressed.into_wide_iter()).collect();
+    let bits_per_char = encoding.bits_per_char();
+
+    let mut tokenizer = Tokenizer::new(values.iter().copied(), bits_per_char);
+    let mut output = Vec::new();
+    decompress_tokens(&mut tokenizer, &mut output)
+        .1
+        .map_err(TrailerError::InvalidData)?;
+
+    // An empty input has no close code.
+    if values.is_empty() {
+        return Ok((output, None));
+    }
+
+    // The compressor always writes at least one bit of padding after the close code.
+    let consumed = tokenizer.bit_position() / usize::from(bits_per_char) + 1;
+    let trailer = match values
+        .get(consumed..)
+        .and_then(|values| Trailer::from_values(values, bits_per_char))
+    {
+        Some(trailer) => trailer,
+        None => return Ok((output, None)),
+    };
+
+    if Trailer::new(&output, trailer.version) != trailer {
+        return Err(TrailerError::Mismatch(trailer));
+    }
+
+    Ok((output, Some(trailer)))
+}
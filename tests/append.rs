@@ -0,0 +1,79 @@
+use lz_str::{append, compress_internal_with_level, decompress, CompressionLevel, Encoding};
+use rand::Rng;
+
+fn compress_with(data: &[u16], encoding: Encoding) -> Vec<u16> {
+    match encoding {
+        Encoding::Raw => lz_str::compress(data),
+        Encoding::Utf16 => lz_str::compress_to_utf16(data).encode_utf16().collect(),
+        Encoding::EncodedUriComponent => lz_str::compress_to_encoded_uri_component(data)
+            .encode_utf16()
+            .collect(),
+        Encoding::Base64 => lz_str::compress_to_base64(data).encode_utf16().collect(),
+    }
+}
+
+#[test]
+fn append_matches_compress() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let len = rng.gen_range(0..1000);
+        let data: Vec<u16> = (0..len)
+            .map(|_| {
+                if rng.gen_bool(0.02) {
+                    rng.gen_range(0..0xD800)
+                } else {
+                    rng.gen_range(u16::from(b'a')..u16::from(b'e'))
+                }
+            })
+            .collect();
+        let split = rng.gen_range(0..=data.len());
+
+        for encoding in [
+            Encoding::Raw,
+            Encoding::Utf16,
+            Encoding::EncodedUriComponent,
+            Encoding::Base64,
+        ] {
+            let compressed = compress_with(&data[..split], encoding);
+            let appended = append(&compressed, encoding, &data[split..]).expect("valid data");
+            assert_eq!(appended, compress_with(&data, encoding), "{:?}", encoding);
+        }
+    }
+}
+
+#[test]
+fn append_repeatedly() {
+    let events: Vec<String> = (0..50)
+        .map(|i| format!("{{\"event\":\"click\",\"id\":{}}}\n", i))
+        .collect();
+
+    let mut compressed: Vec<u16> = lz_str::compress_to_utf16("").encode_utf16().collect();
+    for (i, event) in events.iter().enumerate() {
+        compressed = append(&compressed, Encoding::Utf16, event.as_str()).expect("valid data");
+        let expected = lz_str::compress_to_utf16(events[..=i].concat().as_str());
+        assert_eq!(String::from_utf16(&compressed).unwrap(), expected);
+    }
+}
+
+#[test]
+fn append_best() {
+    let data: Vec<u16> = "abababababcabcabcabcabababcbcbcbcbcbabcabcabcbabcbabcabcbabcabca"
+        .encode_utf16()
+        .collect();
+    let compressed =
+        compress_internal_with_level(&data, 16, std::convert::identity, CompressionLevel::Best);
+
+    let appended = append(&compressed, Encoding::Raw, "abcabcabc").expect("valid data");
+    let mut expected = data.clone();
+    expected.extend("abcabcabc".encode_utf16());
+    assert_eq!(decompress(&appended), Some(expected));
+}
+
+#[test]
+fn append_invalid() {
+    assert_eq!(append("bed123", Encoding::Raw, "abc"), None);
+    assert_eq!(
+        append("", Encoding::Raw, "abc"),
+        Some(lz_str::compress("abc"))
+    );
+}
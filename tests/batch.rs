@@ -0,0 +1,106 @@
+#![cfg(feature = "rayon")]
+
+use lz_str::{compress_batch, decompress_batch, Encoding};
+use rand::{Rng, SeedableRng};
+
+const ENCODINGS: [Encoding; 4] = [
+    Encoding::Raw,
+    Encoding::Utf16,
+    Encoding::EncodedUriComponent,
+    Encoding::Base64,
+];
+
+fn compress(data: &[u16], encoding: Encoding) -> Vec<u16> {
+    match encoding {
+        Encoding::Raw => lz_str::compress(data),
+        Encoding::Utf16 => lz_str::compress_to_utf16(data).encode_utf16().collect(),
+        Encoding::EncodedUriComponent => lz_str::compress_to_encoded_uri_component(data)
+            .encode_utf16()
+            .collect(),
+        Encoding::Base64 => lz_str::compress_to_base64(data).encode_utf16().collect(),
+    }
+}
+
+fn decompress(compressed: &[u16], encoding: Encoding) -> Option<Vec<u16>> {
+    match encoding {
+        Encoding::Raw => lz_str::decompress(compressed),
+        _ => {
+            let compressed = String::from_utf16(compressed).unwrap();
+            match encoding {
+                Encoding::Utf16 => lz_str::decompress_from_utf16(&compressed),
+                Encoding::EncodedUriComponent => {
+                    lz_str::decompress_from_encoded_uri_component(&compressed)
+                }
+                _ => lz_str::decompress_from_base64(&compressed),
+            }
+        }
+    }
+}
+
+fn random_inputs(rng: &mut impl Rng) -> Vec<Vec<u16>> {
+    (0..500)
+        .map(|_| {
+            let len = rng.gen_range(0..200);
+            (0..len)
+                .map(|_| {
+                    if rng.gen_bool(0.9) {
+                        rng.gen_range(b'a'..b'f').into()
+                    } else {
+                        rng.gen()
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn batch_matches_compress() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(45);
+    let inputs = random_inputs(&mut rng);
+    for encoding in ENCODINGS {
+        let compressed = compress_batch(&inputs, encoding);
+        assert_eq!(compressed.len(), inputs.len());
+        for (input, compressed) in inputs.iter().zip(&compressed) {
+            assert_eq!(*compressed, compress(input, encoding));
+        }
+
+        let decompressed = decompress_batch(&compressed, encoding);
+        assert_eq!(decompressed.len(), inputs.len());
+        for (input, decompressed) in inputs.iter().zip(decompressed) {
+            assert_eq!(decompressed.as_ref(), Ok(input));
+        }
+    }
+
+    assert!(compress_batch::<String>(&[], Encoding::Base64).is_empty());
+    assert!(decompress_batch::<String>(&[], Encoding::Base64).is_empty());
+}
+
+#[test]
+fn batch_matches_decompress() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(46);
+    let inputs = random_inputs(&mut rng);
+    for encoding in ENCODINGS {
+        // Damage some of the inputs.
+        let compressed: Vec<Vec<u16>> = inputs
+            .iter()
+            .map(|input| {
+                let mut compressed = compress(input, encoding);
+                if rng.gen_bool(0.5) && !compressed.is_empty() {
+                    let len = rng.gen_range(0..compressed.len());
+                    compressed.truncate(len);
+                }
+                compressed
+            })
+            .collect();
+
+        let decompressed = decompress_batch(&compressed, encoding);
+        for (compressed, decompressed) in compressed.iter().zip(decompressed) {
+            assert_eq!(decompressed.ok(), decompress(compressed, encoding));
+        }
+    }
+
+    // The first code references an entry that does not exist.
+    let decompressed = decompress_batch(&[vec![0xFFFF_u16]], Encoding::Raw);
+    assert!(decompressed[0].is_err());
+}
@@ -0,0 +1,89 @@
+use lz_str::{compress_bounded, Boundary, Encoding};
+use rand::Rng;
+
+const ENCODINGS: [Encoding; 4] = [
+    Encoding::Raw,
+    Encoding::Utf16,
+    Encoding::EncodedUriComponent,
+    Encoding::Base64,
+];
+
+fn compress(data: &[u16], encoding: Encoding) -> Vec<u16> {
+    match encoding {
+        Encoding::Raw => lz_str::compress(data),
+        Encoding::Utf16 => lz_str::compress_to_utf16(data).encode_utf16().collect(),
+        Encoding::EncodedUriComponent => lz_str::compress_to_encoded_uri_component(data)
+            .encode_utf16()
+            .collect(),
+        Encoding::Base64 => lz_str::compress_to_base64(data).encode_utf16().collect(),
+    }
+}
+
+#[test]
+fn bounded_longest_prefix() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..5 {
+        let len = rng.gen_range(0..300);
+        let data: Vec<u16> = (0..len)
+            .map(|_| {
+                if rng.gen_bool(0.05) {
+                    0x2603
+                } else {
+                    u16::from(rng.gen_range(b'a'..=b'd'))
+                }
+            })
+            .collect();
+
+        for encoding in ENCODINGS {
+            let sizes: Vec<usize> = (0..=data.len())
+                .map(|i| compress(&data[..i], encoding).len())
+                .collect();
+
+            for max_len in [0, 1, 4, 10, 33, 99, 100, 101, 1000] {
+                let result = compress_bounded(&data, encoding, max_len, Boundary::CodeUnit);
+                if sizes[0] > max_len {
+                    assert_eq!(result, None);
+                    continue;
+                }
+
+                let (compressed, consumed) = result.expect("no output");
+                assert_eq!(compressed, compress(&data[..consumed], encoding));
+                assert!(compressed.len() <= max_len);
+
+                let expected = (0..=data.len())
+                    .rev()
+                    .find(|&i| sizes[i] <= max_len)
+                    .unwrap();
+                assert_eq!(consumed, expected, "{:?} {}", encoding, max_len);
+            }
+        }
+    }
+}
+
+#[test]
+fn bounded_boundaries() {
+    let data = "first line \u{1F600}\nsecond line \u{1F600}\nthird line \u{1F600}\n";
+    let wide: Vec<u16> = data.encode_utf16().collect();
+
+    for max_len in 1..100 {
+        if let Some((_, consumed)) =
+            compress_bounded(data, Encoding::EncodedUriComponent, max_len, Boundary::Line)
+        {
+            assert!(consumed == 0 || wide[consumed - 1] == u16::from(b'\n'));
+        }
+
+        if let Some((_, consumed)) =
+            compress_bounded(data, Encoding::EncodedUriComponent, max_len, Boundary::Char)
+        {
+            assert!(String::from_utf16(&wide[..consumed]).is_ok());
+        }
+    }
+
+    let (compressed, consumed) =
+        compress_bounded(data, Encoding::Base64, 10_000, Boundary::Line).expect("no output");
+    assert_eq!(consumed, wide.len());
+    assert_eq!(
+        String::from_utf16(&compressed).unwrap(),
+        lz_str::compress_to_base64(data)
+    );
+}
@@ -0,0 +1,108 @@
+use lz_str::{compress_chunked, ChunkedReader, Encoding, FrameError};
+use rand::Rng;
+
+const ENCODINGS: [Encoding; 4] = [
+    Encoding::Raw,
+    Encoding::Utf16,
+    Encoding::EncodedUriComponent,
+    Encoding::Base64,
+];
+
+#[test]
+fn chunked_round_trip() {
+    let mut rng = rand::thread_rng();
+    let data: String = (0..10_000).map(|_| rng.gen_range('a'..='f')).collect();
+    let expected: Vec<u16> = data.encode_utf16().collect();
+
+    for encoding in ENCODINGS {
+        for chunk_len in [1, 777, 10_000, 20_000] {
+            let container = compress_chunked(&data, encoding, chunk_len);
+            let reader = ChunkedReader::new(&container).expect("invalid container");
+
+            assert_eq!(reader.encoding(), encoding);
+            assert_eq!(reader.len(), expected.len());
+            assert_eq!(reader.num_chunks(), expected.len().div_ceil(chunk_len));
+            assert_eq!(reader.decompress(), Ok(expected.clone()));
+        }
+    }
+
+    let container = compress_chunked("", Encoding::Base64, 10);
+    let reader = ChunkedReader::new(&container).expect("invalid container");
+    assert!(reader.is_empty());
+    assert_eq!(reader.decompress(), Ok(Vec::new()));
+}
+
+#[test]
+fn chunked_extract() {
+    let mut rng = rand::thread_rng();
+    let data: String = (0..5_000).map(|_| rng.gen_range('a'..='f')).collect();
+    let expected: Vec<u16> = data.encode_utf16().collect();
+
+    let container = compress_chunked(&data, Encoding::Base64, 300);
+    let reader = ChunkedReader::new(&container).expect("invalid container");
+    for _ in 0..100 {
+        let start = rng.gen_range(0..=expected.len());
+        let end = rng.gen_range(start..=expected.len());
+        assert_eq!(
+            reader.extract(start..end),
+            Ok(expected[start..end].to_vec())
+        );
+    }
+
+    assert_eq!(
+        reader.extract(0..expected.len() + 1),
+        Err(FrameError::OutOfBounds)
+    );
+}
+
+#[test]
+fn chunked_errors() {
+    let container = compress_chunked(
+        "The quick brown fox jumps over the lazy dog",
+        Encoding::Base64,
+        10,
+    );
+
+    assert_eq!(
+        ChunkedReader::new(&container[..5]).err(),
+        Some(FrameError::TooShort)
+    );
+    assert_eq!(
+        ChunkedReader::new(&container[..container.len() - 1]).err(),
+        Some(FrameError::TooShort)
+    );
+
+    let mut bad = container.clone();
+    bad[0] = b'X';
+    assert_eq!(
+        ChunkedReader::new(&bad).err(),
+        Some(FrameError::InvalidMagic)
+    );
+
+    // Damage the length of the second chunk.
+    let mut bad = container;
+    bad[10 + 16 + 8] ^= 1;
+    let reader = ChunkedReader::new(&bad).expect("invalid container");
+    assert_eq!(reader.extract(0..5), Ok("The q".encode_utf16().collect()));
+    assert!(matches!(
+        reader.extract(5..15),
+        Err(FrameError::LengthMismatch { .. })
+    ));
+}
+
+#[test]
+fn chunked_huge_claimed_len() {
+    let container = compress_chunked("", Encoding::Base64, 10);
+    for decompressed_len in [1_u64 << 62, 1 << 40, u64::from(u32::MAX)] {
+        // A single empty chunk that claims to decompress to far more than it can.
+        let mut bad = container[..6].to_vec();
+        bad.extend(1_u32.to_le_bytes());
+        bad.extend(0_u64.to_le_bytes());
+        bad.extend(decompressed_len.to_le_bytes());
+        assert_eq!(bad.len(), 26);
+
+        let reader = ChunkedReader::new(&bad).expect("invalid container");
+        assert!(reader.decompress().is_err());
+        assert!(reader.extract(0..1 << 30).is_err());
+    }
+}
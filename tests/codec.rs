@@ -0,0 +1,198 @@
+#![cfg(feature = "tokio-util")]
+
+use bytes::BytesMut;
+use futures::executor::block_on;
+use futures::{SinkExt, StreamExt};
+use lz_str::{CodecError, Encoding, Framing, LzStringCodec};
+use tokio_util::codec::{Decoder, Encoder, Framed, FramedRead};
+
+const MESSAGES: [&str; 4] = [
+    "The quick brown fox jumps over the lazy dog",
+    "",
+    "\u{2603}\u{1F600}",
+    "abcabcabcabcabcabcabcabcabcabcabcabc",
+];
+
+/// Every encoding with every framing that works with it.
+fn codecs() -> Vec<LzStringCodec> {
+    vec![
+        LzStringCodec::new(Encoding::Raw, Framing::LengthPrefix),
+        LzStringCodec::new(Encoding::Utf16, Framing::LengthPrefix),
+        LzStringCodec::new(Encoding::Utf16, Framing::Delimiter(b'\n')),
+        LzStringCodec::new(Encoding::Utf16, Framing::Delimiter(b'\0')),
+        LzStringCodec::new(Encoding::EncodedUriComponent, Framing::LengthPrefix),
+        LzStringCodec::new(Encoding::EncodedUriComponent, Framing::Delimiter(b'\n')),
+        LzStringCodec::new(Encoding::Base64, Framing::LengthPrefix),
+        LzStringCodec::new(Encoding::Base64, Framing::Delimiter(b'\n')),
+        LzStringCodec::new(Encoding::Base64, Framing::Delimiter(b'.')),
+    ]
+}
+
+#[test]
+fn codec_duplex() {
+    for codec in codecs() {
+        let (client, server) = tokio::io::duplex(4096);
+        let mut client = Framed::new(client, codec.clone());
+        let mut server = Framed::new(server, codec);
+
+        block_on(async {
+            for message in MESSAGES {
+                client.send(message).await.unwrap();
+                assert_eq!(server.next().await.unwrap().unwrap(), message);
+
+                server.send(message).await.unwrap();
+                assert_eq!(client.next().await.unwrap().unwrap(), message);
+            }
+
+            drop(client);
+            assert!(server.next().await.is_none());
+        });
+    }
+}
+
+#[test]
+fn codec_matches_compress() {
+    let mut codec = LzStringCodec::new(Encoding::Utf16, Framing::Delimiter(b'\n'));
+    let mut buffer = BytesMut::new();
+    for message in MESSAGES {
+        codec.encode(message, &mut buffer).unwrap();
+    }
+
+    let expected: String = MESSAGES
+        .iter()
+        .map(|message| format!("{}\n", lz_str::compress_to_utf16(*message)))
+        .collect();
+    assert_eq!(&buffer[..], expected.as_bytes());
+
+    let mut codec = LzStringCodec::new(Encoding::Raw, Framing::LengthPrefix);
+    let mut buffer = BytesMut::new();
+    codec.encode(MESSAGES[0], &mut buffer).unwrap();
+
+    let compressed = lz_str::compress_to_uint8_array(MESSAGES[0]);
+    assert_eq!(&buffer[..4], (compressed.len() as u32).to_be_bytes());
+    assert_eq!(&buffer[4..], compressed);
+}
+
+#[test]
+fn codec_pieces() {
+    for mut codec in codecs() {
+        let mut encoded = BytesMut::new();
+        for message in MESSAGES {
+            codec.encode(message, &mut encoded).unwrap();
+        }
+
+        // Feed the messages one byte at a time.
+        let mut buffer = BytesMut::new();
+        let mut decoded = Vec::new();
+        for b in encoded.iter() {
+            buffer.extend_from_slice(&[*b]);
+            while let Some(message) = codec.decode(&mut buffer).unwrap() {
+                decoded.push(message);
+            }
+        }
+        assert!(buffer.is_empty());
+        assert_eq!(decoded, MESSAGES);
+    }
+}
+
+#[test]
+fn codec_eof() {
+    // The last message may not have a delimiter.
+    let data = lz_str::compress_to_base64(MESSAGES[0]);
+    let mut reader = FramedRead::new(
+        data.as_bytes(),
+        LzStringCodec::new(Encoding::Base64, Framing::Delimiter(b'\n')),
+    );
+    block_on(async {
+        assert_eq!(reader.next().await.unwrap().unwrap(), MESSAGES[0]);
+        assert!(reader.next().await.is_none());
+    });
+
+    // A length prefixed message may not be cut short.
+    let mut codec = LzStringCodec::new(Encoding::Base64, Framing::LengthPrefix);
+    let mut encoded = BytesMut::new();
+    codec.encode(MESSAGES[0], &mut encoded).unwrap();
+    let mut reader = FramedRead::new(&encoded[..encoded.len() - 1], codec);
+    block_on(async {
+        assert!(matches!(
+            reader.next().await,
+            Some(Err(CodecError::Io(error))) if error.kind() == std::io::ErrorKind::UnexpectedEof
+        ));
+    });
+}
+
+#[test]
+fn codec_errors() {
+    let mut codec =
+        LzStringCodec::new(Encoding::Base64, Framing::Delimiter(b'\n')).with_max_len(10);
+    let mut buffer = BytesMut::new();
+    assert!(matches!(
+        codec.encode(MESSAGES[0], &mut buffer),
+        Err(CodecError::TooLong { max_len: 10, .. })
+    ));
+    assert!(buffer.is_empty());
+
+    // The limit applies before the delimiter is found.
+    let mut buffer = BytesMut::from(&b"AAAAAAAAAAAA"[..]);
+    assert!(matches!(
+        codec.decode(&mut buffer),
+        Err(CodecError::TooLong {
+            len: 12,
+            max_len: 10
+        })
+    ));
+
+    let mut codec = LzStringCodec::new(Encoding::Raw, Framing::LengthPrefix).with_max_len(10);
+    let mut buffer = BytesMut::from(&[0, 0, 1, 0][..]);
+    assert!(matches!(
+        codec.decode(&mut buffer),
+        Err(CodecError::TooLong {
+            len: 256,
+            max_len: 10
+        })
+    ));
+
+    // Raw messages have an even number of bytes.
+    let mut buffer = BytesMut::from(&[0, 0, 0, 1, 0][..]);
+    assert!(matches!(
+        codec.decode(&mut buffer),
+        Err(CodecError::InvalidPayload)
+    ));
+
+    let mut codec = LzStringCodec::new(Encoding::Utf16, Framing::Delimiter(b'\n'));
+    let mut buffer = BytesMut::from(&b"\xFF\n"[..]);
+    assert!(matches!(
+        codec.decode(&mut buffer),
+        Err(CodecError::InvalidPayload)
+    ));
+
+    let compressed = lz_str::compress_to_base64(MESSAGES[0]);
+    let mut codec = LzStringCodec::new(Encoding::Base64, Framing::Delimiter(b'\n'));
+    let mut buffer =
+        BytesMut::from(format!("{}\n", &compressed[..compressed.len() / 2]).as_bytes());
+    assert!(matches!(
+        codec.decode(&mut buffer),
+        Err(CodecError::InvalidData(_))
+    ));
+
+    // Data after the stream is rejected, not ignored.
+    let mut buffer = BytesMut::from(format!("{compressed}AAAA\n").as_bytes());
+    assert!(matches!(
+        codec.decode(&mut buffer),
+        Err(CodecError::InvalidPayload)
+    ));
+
+    // The decompressed message is a lone surrogate.
+    let compressed = lz_str::compress_to_base64(&[0xD800][..]);
+    let mut buffer = BytesMut::from(format!("{compressed}\n").as_bytes());
+    assert!(matches!(
+        codec.decode(&mut buffer),
+        Err(CodecError::InvalidUtf16)
+    ));
+}
+
+#[test]
+#[should_panic]
+fn codec_invalid_delimiter() {
+    LzStringCodec::new(Encoding::Base64, Framing::Delimiter(b'A'));
+}
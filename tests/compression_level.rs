@@ -0,0 +1,69 @@
+use lz_str::{
+    compress_internal, compress_internal_with_level, decompress_internal, CompressionLevel,
+};
+use rand::Rng;
+
+const TEST_PHRASE: &str = "During tattooing, ink is injected into the skin, initiating an immune response, and cells called \"macrophages\" move into the area and \"eat up\" the ink. The macrophages carry some of the ink to the body\'s lymph nodes, but some that are filled with ink stay put, embedded in the skin. That\'s what makes the tattoo visible under the skin.";
+
+fn check_ratio(data: &[u16]) -> (usize, usize) {
+    let default = compress_internal(data, 16, std::convert::identity);
+    let best =
+        compress_internal_with_level(data, 16, std::convert::identity, CompressionLevel::Best);
+
+    let decompressed = decompress_internal(best.iter().copied(), 16).expect("valid decompress");
+    assert_eq!(decompressed, data);
+
+    (default.len(), best.len())
+}
+
+#[test]
+fn default_level_matches_compress_internal() {
+    let data: Vec<u16> = TEST_PHRASE.encode_utf16().collect();
+    assert_eq!(
+        compress_internal_with_level(&data, 6, |n| n, CompressionLevel::Default),
+        compress_internal(&data, 6, |n| n)
+    );
+}
+
+#[test]
+fn best_round_trip() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let len = rng.gen_range(0..500);
+        let alphabet_len = rng.gen_range(1..8);
+        let data: Vec<u16> = (0..len)
+            .map(|_| {
+                if rng.gen_bool(0.01) {
+                    rng.gen()
+                } else {
+                    rng.gen_range(0..alphabet_len) + u16::from(b'a')
+                }
+            })
+            .collect();
+
+        for bits_per_char in [6, 15, 16] {
+            let compressed = compress_internal_with_level(
+                &data,
+                bits_per_char,
+                std::convert::identity,
+                CompressionLevel::Best,
+            );
+            let decompressed = decompress_internal(compressed.into_iter(), bits_per_char)
+                .expect("valid decompress");
+            assert_eq!(decompressed, data);
+        }
+    }
+}
+
+#[test]
+fn best_ratio() {
+    let data: Vec<u16> = TEST_PHRASE.encode_utf16().collect();
+    let (default, best) = check_ratio(&data);
+    assert!(best <= default, "{} > {}", best, default);
+
+    let data: Vec<u16> = include_str!("../test_data/long_compressed_js.txt")
+        .encode_utf16()
+        .collect();
+    let (default, best) = check_ratio(&data);
+    assert!(best < default, "{} >= {}", best, default);
+}
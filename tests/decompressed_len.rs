@@ -0,0 +1,58 @@
+use lz_str::{decompressed_len, Encoding};
+use rand::Rng;
+
+#[test]
+fn decompressed_len_matches_decompress() {
+    let mut rng = rand::thread_rng();
+    let mut inputs = vec![
+        String::new(),
+        "a".to_string(),
+        "aaaaabaaaaacaaaaadaaaaaeaaaaa".to_string(),
+        "The quick brown fox jumps over the lazy dog".to_string(),
+        "\u{1F600}\u{1F600}\u{1F600} snowman \u{2603}".to_string(),
+    ];
+    for _ in 0..20 {
+        let len = rng.gen_range(0..2000);
+        inputs.push((0..len).map(|_| rng.gen_range('a'..='e')).collect());
+    }
+
+    for input in inputs {
+        let expected = input.encode_utf16().count();
+
+        let compressed = lz_str::compress(&input);
+        assert_eq!(decompressed_len(Encoding::Raw, &compressed), Some(expected));
+
+        let compressed = lz_str::compress_to_utf16(&input);
+        assert_eq!(
+            decompressed_len(Encoding::Utf16, &compressed),
+            Some(expected)
+        );
+
+        let compressed = lz_str::compress_to_encoded_uri_component(&input);
+        assert_eq!(
+            decompressed_len(Encoding::EncodedUriComponent, &compressed),
+            Some(expected)
+        );
+
+        let compressed = lz_str::compress_to_base64(&input);
+        assert_eq!(
+            decompressed_len(Encoding::Base64, &compressed),
+            Some(expected)
+        );
+    }
+}
+
+#[test]
+fn decompressed_len_invalid() {
+    assert_eq!(decompressed_len(Encoding::Raw, ""), Some(0));
+
+    for data in ["bed123", "zed123", "ed[[[[d1d[[[[dF9]"] {
+        assert_eq!(decompressed_len(Encoding::Raw, data), None);
+    }
+
+    let compressed = lz_str::compress_to_base64("The quick brown fox jumps over the lazy dog");
+    assert_eq!(
+        decompressed_len(Encoding::Base64, &compressed[..compressed.len() / 2]),
+        None
+    );
+}
@@ -0,0 +1,99 @@
+use rand::{Rng, SeedableRng};
+
+/// Compress through the `u16` path, then convert like the encoders used to.
+fn compress_with_key(data: &[u16], bits_per_char: u8, key: Option<&[u8]>) -> Vec<u16> {
+    lz_str::compress_internal(data, bits_per_char, |n| match key {
+        Some(key) => u16::from(key[usize::from(n)]),
+        None => n + 32,
+    })
+}
+
+#[test]
+fn encoders_match_compress_internal() {
+    const URI_KEY: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+-$";
+    const BASE64_KEY: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=";
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(46);
+    for _ in 0..200 {
+        let len = rng.gen_range(0..500);
+        let data: Vec<u16> = (0..len)
+            .map(|_| {
+                if rng.gen_bool(0.8) {
+                    rng.gen_range(b'a'..b'h').into()
+                } else {
+                    rng.gen()
+                }
+            })
+            .collect();
+
+        let mut expected = compress_with_key(&data, 15, None);
+        expected.push(u16::from(b' '));
+        assert_eq!(
+            lz_str::compress_to_utf16(&data),
+            String::from_utf16(&expected).unwrap()
+        );
+
+        let expected = compress_with_key(&data, 6, Some(URI_KEY));
+        assert_eq!(
+            lz_str::compress_to_encoded_uri_component(&data),
+            String::from_utf16(&expected).unwrap()
+        );
+
+        let mut expected = compress_with_key(&data, 6, Some(BASE64_KEY));
+        match expected.len() % 4 {
+            0 => {}
+            mod_4 => expected.resize(expected.len() + 5 - mod_4, u16::from(b'=')),
+        }
+        assert_eq!(
+            lz_str::compress_to_base64(&data),
+            String::from_utf16(&expected).unwrap()
+        );
+
+        let expected: Vec<u8> = lz_str::compress(&data)
+            .into_iter()
+            .flat_map(|c| c.to_be_bytes())
+            .collect();
+        assert_eq!(lz_str::compress_to_uint8_array(&data), expected);
+    }
+}
+
+#[test]
+fn decoders_skip_chars_outside_alphabet() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(47);
+    for _ in 0..200 {
+        let len = rng.gen_range(0..500);
+        let data: Vec<u16> = (0..len).map(|_| rng.gen_range(b'a'..b'h').into()).collect();
+
+        // Insert chars that are not part of the alphabets, including multi-byte ones.
+        let noise = ['\n', '!', '~', '\u{e9}', '\u{2603}', '\u{1f600}'];
+        let mut add_noise = |compressed: String| {
+            let mut noisy = String::new();
+            for c in compressed.chars() {
+                if rng.gen_bool(0.1) {
+                    noisy.push(noise[rng.gen_range(0..noise.len())]);
+                }
+                noisy.push(c);
+            }
+            noisy
+        };
+
+        let compressed = add_noise(lz_str::compress_to_base64(&data));
+        assert_eq!(
+            lz_str::decompress_from_base64(&compressed),
+            Some(data.clone())
+        );
+
+        let compressed = add_noise(lz_str::compress_to_encoded_uri_component(&data));
+        assert_eq!(
+            lz_str::decompress_from_encoded_uri_component(&compressed),
+            Some(data.clone())
+        );
+
+        // Spaces may have been decoded from a '+'.
+        let compressed = lz_str::compress_to_encoded_uri_component(&data).replace('+', " ");
+        assert_eq!(
+            lz_str::decompress_from_encoded_uri_component(&compressed),
+            Some(data)
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use lz_str::{
+    decompress_into, DictionaryEntry, Encoding, FixedDecompressor, FixedError, TokenError,
+};
+use rand::{Rng, SeedableRng};
+
+const DATA: &str =
+    "The quick brown fox jumps over the lazy dog. The quick brown fox jumps over the lazy dog.";
+
+fn compress(data: &[u16], encoding: Encoding) -> Vec<u16> {
+    match encoding {
+        Encoding::Raw => lz_str::compress(data),
+        Encoding::Utf16 => lz_str::compress_to_utf16(data).encode_utf16().collect(),
+        Encoding::EncodedUriComponent => lz_str::compress_to_encoded_uri_component(data)
+            .encode_utf16()
+            .collect(),
+        Encoding::Base64 => lz_str::compress_to_base64(data).encode_utf16().collect(),
+    }
+}
+
+const ENCODINGS: [Encoding; 4] = [
+    Encoding::Raw,
+    Encoding::Utf16,
+    Encoding::EncodedUriComponent,
+    Encoding::Base64,
+];
+
+/// Decompress with buffers of the given sizes.
+fn decompress(
+    compressed: &[u16],
+    encoding: Encoding,
+    output_len: usize,
+    dictionary_len: usize,
+) -> Result<Vec<u16>, FixedError> {
+    let mut output = vec![0; output_len];
+    let mut dictionary = vec![DictionaryEntry::EMPTY; dictionary_len];
+    let len = decompress_into(compressed, encoding, &mut output, &mut dictionary)?;
+    output.truncate(len);
+    Ok(output)
+}
+
+#[test]
+fn fixed_matches_decompress() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+    for _ in 0..100 {
+        let len = rng.gen_range(0..500);
+        // Few distinct chars make for long entries.
+        let data: Vec<u16> = (0..len)
+            .map(|_| {
+                if rng.gen_bool(0.9) {
+                    rng.gen_range(b'a'..b'e').into()
+                } else {
+                    rng.gen()
+                }
+            })
+            .collect();
+
+        for encoding in ENCODINGS {
+            let compressed = compress(&data, encoding);
+            let decompressed = decompress(&compressed, encoding, data.len(), data.len() * 2)
+                .expect("invalid data");
+            assert_eq!(decompressed, data);
+        }
+    }
+
+    for encoding in ENCODINGS {
+        assert_eq!(decompress(&[], encoding, 0, 0), Ok(Vec::new()));
+    }
+}
+
+#[test]
+fn fixed_capacity() {
+    let data: Vec<u16> = DATA.encode_utf16().collect();
+    for encoding in ENCODINGS {
+        let compressed = compress(&data, encoding);
+
+        assert_eq!(
+            decompress(&compressed, encoding, data.len() - 1, data.len() * 2),
+            Err(FixedError::OutputFull)
+        );
+        assert_eq!(
+            decompress(&compressed, encoding, data.len(), 8),
+            Err(FixedError::DictionaryFull)
+        );
+
+        // The smallest dictionary that fits.
+        let dictionary_len = (0..data.len() * 2)
+            .find(|&len| decompress(&compressed, encoding, data.len(), len).is_ok())
+            .unwrap();
+        assert_eq!(
+            decompress(&compressed, encoding, data.len(), dictionary_len - 1),
+            Err(FixedError::DictionaryFull)
+        );
+    }
+}
+
+#[test]
+fn fixed_invalid() {
+    let data: Vec<u16> = DATA.encode_utf16().collect();
+    for encoding in ENCODINGS {
+        let compressed = compress(&data, encoding);
+        let truncated = &compressed[..compressed.len() / 2];
+        assert_eq!(
+            decompress(truncated, encoding, data.len(), data.len() * 2),
+            Err(FixedError::InvalidData(TokenError::UnexpectedEnd))
+        );
+    }
+
+    // The first code references an entry that does not exist.
+    assert!(matches!(
+        decompress(&[0xFFFF], Encoding::Raw, 16, 16),
+        Err(FixedError::InvalidData(TokenError::InvalidReference(_)))
+    ));
+}
+
+#[test]
+fn fixed_decompressor() {
+    let data: Vec<u16> = DATA.encode_utf16().collect();
+    let mut decompressor = FixedDecompressor::<128, 256>::new();
+    for encoding in ENCODINGS {
+        let compressed = compress(&data, encoding);
+        assert_eq!(
+            decompressor.decompress(&compressed, encoding),
+            Ok(&data[..])
+        );
+    }
+
+    // The buffers are reused.
+    let compressed = lz_str::compress_to_base64("abc");
+    assert_eq!(
+        decompressor.decompress(&compressed, Encoding::Base64),
+        Ok(&[u16::from(b'a'), u16::from(b'b'), u16::from(b'c')][..])
+    );
+}
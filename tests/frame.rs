@@ -0,0 +1,101 @@
+use lz_str::{decode_framed, encode_framed, Encoding, FrameError, FrameHeader};
+
+const ENCODINGS: [Encoding; 4] = [
+    Encoding::Raw,
+    Encoding::Utf16,
+    Encoding::EncodedUriComponent,
+    Encoding::Base64,
+];
+
+const DATA: &str = "The quick brown fox jumps over the lazy dog \u{2603}\u{1F600}";
+
+#[test]
+fn frame_round_trip() {
+    for input in ["", DATA] {
+        let expected: Vec<u16> = input.encode_utf16().collect();
+        for encoding in ENCODINGS {
+            let frame = encode_framed(input, encoding);
+            assert_eq!(decode_framed(&frame), Ok(expected.clone()));
+
+            let (header, payload) = FrameHeader::read(&frame).expect("invalid header");
+            assert_eq!(header.encoding, encoding);
+            assert_eq!(header.len, expected.len() as u64);
+
+            // The payload is the output of the plain compress function.
+            let plain = match encoding {
+                Encoding::Raw => lz_str::decompress_from_uint8_array(payload),
+                Encoding::Utf16 => {
+                    lz_str::decompress_from_utf16(std::str::from_utf8(payload).unwrap())
+                }
+                Encoding::EncodedUriComponent => lz_str::decompress_from_encoded_uri_component(
+                    std::str::from_utf8(payload).unwrap(),
+                ),
+                Encoding::Base64 => {
+                    lz_str::decompress_from_base64(std::str::from_utf8(payload).unwrap())
+                }
+            };
+            assert_eq!(plain, Some(expected.clone()));
+        }
+    }
+}
+
+#[test]
+fn frame_errors() {
+    let frame = encode_framed(DATA, Encoding::Base64);
+
+    assert_eq!(decode_framed(&frame[..10]), Err(FrameError::TooShort));
+
+    let mut bad = frame.clone();
+    bad[0] = b'X';
+    assert_eq!(decode_framed(&bad), Err(FrameError::InvalidMagic));
+
+    let mut bad = frame.clone();
+    bad[4] = 9;
+    assert_eq!(decode_framed(&bad), Err(FrameError::UnsupportedVersion(9)));
+
+    let mut bad = frame.clone();
+    bad[5] = 9;
+    assert_eq!(decode_framed(&bad), Err(FrameError::UnknownEncoding(9)));
+
+    let mut bad = frame.clone();
+    bad[6] ^= 1;
+    assert!(matches!(
+        decode_framed(&bad),
+        Err(FrameError::LengthMismatch { .. })
+    ));
+
+    let mut bad = frame.clone();
+    bad[14] ^= 1;
+    assert!(matches!(
+        decode_framed(&bad),
+        Err(FrameError::ChecksumMismatch { .. })
+    ));
+
+    let mut bad = frame.clone();
+    bad.truncate(frame.len() / 2);
+    assert_eq!(
+        decode_framed(&bad),
+        Err(FrameError::InvalidData(lz_str::TokenError::UnexpectedEnd))
+    );
+
+    let mut bad = frame;
+    bad.push(0xFF);
+    assert_eq!(decode_framed(&bad), Err(FrameError::InvalidPayload));
+}
+
+#[test]
+fn frame_trailing_data() {
+    for encoding in ENCODINGS {
+        // The header still matches, as the data after the stream is never decompressed.
+        let mut bad = encode_framed(DATA, encoding);
+        match encoding {
+            Encoding::Raw => bad.extend([0, 0]),
+            _ => bad.extend(b"AAAA"),
+        }
+        assert_eq!(
+            decode_framed(&bad),
+            Err(FrameError::InvalidPayload),
+            "{encoding:?}"
+        );
+    }
+}
@@ -0,0 +1,160 @@
+#![cfg(feature = "futures-io")]
+
+use futures::executor::block_on;
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use lz_str::futures::{CompressReader, CompressWriter, DecompressReader, DecompressWriter};
+use lz_str::Encoding;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+const ENCODINGS: [Encoding; 4] = [
+    Encoding::Raw,
+    Encoding::Utf16,
+    Encoding::EncodedUriComponent,
+    Encoding::Base64,
+];
+
+const PIECE_LENS: [usize; 4] = [1, 3, 100, 100_000];
+
+fn data() -> String {
+    "The quick brown fox jumps over the lazy dog \u{2603}\u{1F600} ".repeat(100)
+}
+
+fn compress(data: &str, encoding: Encoding) -> Vec<u8> {
+    match encoding {
+        Encoding::Raw => lz_str::compress_to_uint8_array(data),
+        Encoding::Utf16 => lz_str::compress_to_utf16(data).into_bytes(),
+        Encoding::EncodedUriComponent => {
+            lz_str::compress_to_encoded_uri_component(data).into_bytes()
+        }
+        Encoding::Base64 => lz_str::compress_to_base64(data).into_bytes(),
+    }
+}
+
+/// A reader that reads at most `piece_len` bytes at a time.
+struct Pieces<'a> {
+    data: &'a [u8],
+    piece_len: usize,
+}
+
+impl AsyncRead for Pieces<'_> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let len = self.piece_len.min(self.data.len()).min(buf.len());
+        let (piece, rest) = self.data.split_at(len);
+        buf[..len].copy_from_slice(piece);
+        self.data = rest;
+        Poll::Ready(Ok(len))
+    }
+}
+
+/// Read all of `reader` with reads of at most `piece_len` bytes.
+async fn read_all(mut reader: impl AsyncRead + Unpin, piece_len: usize) -> io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut buffer = vec![0; piece_len];
+    loop {
+        let len = reader.read(&mut buffer).await?;
+        if len == 0 {
+            return Ok(output);
+        }
+        output.extend(&buffer[..len]);
+    }
+}
+
+#[test]
+fn futures_compress() {
+    let data = data();
+    for encoding in ENCODINGS {
+        let expected = compress(&data, encoding);
+        for piece_len in PIECE_LENS {
+            let reader = CompressReader::new(
+                Pieces {
+                    data: data.as_bytes(),
+                    piece_len,
+                },
+                encoding,
+            );
+            let compressed = block_on(read_all(reader, piece_len)).unwrap();
+            assert_eq!(compressed, expected);
+
+            let mut writer = CompressWriter::new(Vec::new(), encoding);
+            block_on(async {
+                for piece in data.as_bytes().chunks(piece_len) {
+                    writer.write_all(piece).await?;
+                }
+                writer.close().await
+            })
+            .unwrap();
+            assert_eq!(writer.into_inner(), expected);
+        }
+    }
+}
+
+#[test]
+fn futures_decompress() {
+    let data = data();
+    for encoding in ENCODINGS {
+        let compressed = compress(&data, encoding);
+        for piece_len in PIECE_LENS {
+            let reader = DecompressReader::new(
+                Pieces {
+                    data: &compressed,
+                    piece_len,
+                },
+                encoding,
+            );
+            let decompressed = block_on(read_all(reader, piece_len)).unwrap();
+            assert_eq!(decompressed, data.as_bytes());
+
+            let mut writer = DecompressWriter::new(Vec::new(), encoding);
+            block_on(async {
+                for piece in compressed.chunks(piece_len) {
+                    writer.write_all(piece).await?;
+                }
+                writer.close().await
+            })
+            .unwrap();
+            assert_eq!(writer.into_inner(), data.as_bytes());
+        }
+    }
+
+    // Empty data is valid.
+    let reader = DecompressReader::new(&[][..], Encoding::Base64);
+    assert_eq!(block_on(read_all(reader, 10)).unwrap(), b"");
+}
+
+#[test]
+fn futures_invalid() {
+    let data = data();
+    for encoding in ENCODINGS {
+        let compressed = compress(&data, encoding);
+        let truncated = &compressed[..compressed.len() / 2];
+
+        let reader = DecompressReader::new(truncated, encoding);
+        let error = block_on(read_all(reader, 10)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let mut writer = DecompressWriter::new(Vec::new(), encoding);
+        let error = block_on(async {
+            writer.write_all(truncated).await?;
+            writer.close().await
+        })
+        .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    // The text to compress is not valid UTF-8.
+    let reader = CompressReader::new(&[b'a', 0xFF][..], Encoding::Base64);
+    let error = block_on(read_all(reader, 10)).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+    // The decompressed data is not valid UTF-16.
+    let compressed = lz_str::compress_to_uint8_array(&[0xD800][..]);
+    let reader = DecompressReader::new(&compressed[..], Encoding::Raw);
+    let error = block_on(read_all(reader, 10)).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+}
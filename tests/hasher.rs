@@ -0,0 +1,83 @@
+#![cfg(feature = "std")]
+
+use lz_str::{DictionaryHasher, Encoding};
+use rand::{Rng, SeedableRng};
+
+const ENCODINGS: [Encoding; 4] = [
+    Encoding::Raw,
+    Encoding::Utf16,
+    Encoding::EncodedUriComponent,
+    Encoding::Base64,
+];
+
+/// The hashers enabled by the current features.
+fn hashers() -> Vec<DictionaryHasher> {
+    vec![
+        DictionaryHasher::Default,
+        DictionaryHasher::SipHash,
+        #[cfg(feature = "rustc-hash")]
+        DictionaryHasher::FxHash,
+        #[cfg(feature = "ahash")]
+        DictionaryHasher::AHash,
+    ]
+}
+
+/// Compress with the compress function for the encoding, as `u16`s.
+fn compress_with_encoder(input: &[u16], encoding: Encoding) -> Vec<u16> {
+    match encoding {
+        Encoding::Raw => lz_str::compress(input),
+        Encoding::Utf16 => lz_str::compress_to_utf16(input).encode_utf16().collect(),
+        Encoding::EncodedUriComponent => lz_str::compress_to_encoded_uri_component(input)
+            .encode_utf16()
+            .collect(),
+        Encoding::Base64 => lz_str::compress_to_base64(input).encode_utf16().collect(),
+    }
+}
+
+#[test]
+fn every_hasher_gives_the_same_output() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(50);
+    for len in [0, 1, 100, 1024, 1025, 20_000] {
+        let text: Vec<u16> = (0..len).map(|_| rng.gen_range(b'a'..b'k').into()).collect();
+        let random: Vec<u16> = (0..len).map(|_| rng.gen()).collect();
+        for input in [text, random] {
+            for encoding in ENCODINGS {
+                let expected = compress_with_encoder(&input, encoding);
+                for hasher in hashers() {
+                    let compressed = lz_str::compress_with_hasher(&input, encoding, hasher);
+                    assert_eq!(compressed, expected, "{hasher:?} {encoding:?}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn compress_with_hasher_matches_encoders() {
+    let data = "During tattooing, ink is injected into the skin, initiating an immune response.";
+    for hasher in hashers() {
+        let compressed = lz_str::compress_with_hasher(data, Encoding::Base64, hasher);
+        assert_eq!(
+            String::from_utf16(&compressed).unwrap(),
+            lz_str::compress_to_base64(data)
+        );
+
+        let compressed = lz_str::compress_with_hasher(data, Encoding::EncodedUriComponent, hasher);
+        assert_eq!(
+            String::from_utf16(&compressed).unwrap(),
+            lz_str::compress_to_encoded_uri_component(data)
+        );
+    }
+}
+
+#[test]
+fn compress_internal_with_hasher_matches_compress_internal() {
+    let data: Vec<u16> = "hello hello hello world".encode_utf16().collect();
+    let compressed = lz_str::compress_internal_with_hasher(
+        &data,
+        16,
+        |n| n,
+        std::collections::hash_map::RandomState::new(),
+    );
+    assert_eq!(compressed, lz_str::compress_internal(&data, 16, |n| n));
+}
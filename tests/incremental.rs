@@ -0,0 +1,138 @@
+use lz_str::{Compressor, Decompressor, Encoding, TokenError};
+use rand::{Rng, SeedableRng};
+
+fn compress(data: &[u16], encoding: Encoding) -> Vec<u16> {
+    match encoding {
+        Encoding::Raw => lz_str::compress(data),
+        Encoding::Utf16 => lz_str::compress_to_utf16(data).encode_utf16().collect(),
+        Encoding::EncodedUriComponent => lz_str::compress_to_encoded_uri_component(data)
+            .encode_utf16()
+            .collect(),
+        Encoding::Base64 => lz_str::compress_to_base64(data).encode_utf16().collect(),
+    }
+}
+
+const ENCODINGS: [Encoding; 4] = [
+    Encoding::Raw,
+    Encoding::Utf16,
+    Encoding::EncodedUriComponent,
+    Encoding::Base64,
+];
+
+/// Split data into pieces of random lengths.
+fn pieces<'a>(rng: &mut impl Rng, data: &'a [u16]) -> Vec<&'a [u16]> {
+    let mut pieces = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let (piece, next) = rest.split_at(rng.gen_range(0..=rest.len().min(20)));
+        pieces.push(piece);
+        rest = next;
+    }
+    pieces
+}
+
+fn random_data(rng: &mut impl Rng) -> Vec<u16> {
+    let len = rng.gen_range(0..1000);
+    (0..len)
+        .map(|_| {
+            if rng.gen_bool(0.9) {
+                rng.gen_range(b'a'..b'f').into()
+            } else {
+                rng.gen()
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn incremental_matches_compress() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+    for _ in 0..100 {
+        let data = random_data(&mut rng);
+        for encoding in ENCODINGS {
+            let mut compressor = Compressor::new(encoding);
+            let mut compressed = Vec::new();
+            for piece in pieces(&mut rng, &data) {
+                compressor.write(piece, &mut compressed);
+            }
+            compressor.finish(&mut compressed);
+
+            assert_eq!(compressed, compress(&data, encoding));
+        }
+    }
+}
+
+#[test]
+fn incremental_round_trip() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(8);
+    for _ in 0..100 {
+        let data = random_data(&mut rng);
+        for encoding in ENCODINGS {
+            let compressed = compress(&data, encoding);
+
+            let mut decompressor = Decompressor::new(encoding);
+            let mut decompressed = Vec::new();
+            for piece in pieces(&mut rng, &compressed) {
+                decompressor
+                    .write(piece, &mut decompressed)
+                    .expect("invalid data");
+            }
+            assert!(decompressor.is_done());
+            assert_eq!(decompressor.finish(), Ok(()));
+            assert_eq!(decompressed, data);
+        }
+    }
+}
+
+#[test]
+fn incremental_output_is_immediate() {
+    let data: Vec<u16> = "abcabcabcabcabcabc".repeat(10).encode_utf16().collect();
+    let compressed = compress(&data, Encoding::Base64);
+
+    // Data is output as soon as its code is read.
+    let mut decompressor = Decompressor::new(Encoding::Base64);
+    let mut decompressed = Vec::new();
+    for (i, c) in compressed.iter().enumerate() {
+        decompressor.write(&[*c][..], &mut decompressed).unwrap();
+        assert!(data.starts_with(&decompressed));
+        if i == compressed.len() / 2 {
+            assert!(!decompressed.is_empty());
+        }
+    }
+    assert!(decompressor.is_done());
+
+    let mut compressor = Compressor::new(Encoding::Base64);
+    let mut output = Vec::new();
+    compressor.write(&data, &mut output);
+    assert!(!output.is_empty());
+    assert!(compressed.starts_with(&output));
+}
+
+#[test]
+fn incremental_invalid() {
+    let data: Vec<u16> = "The quick brown fox jumps over the lazy dog"
+        .encode_utf16()
+        .collect();
+    for encoding in ENCODINGS {
+        let compressed = compress(&data, encoding);
+
+        let mut decompressor = Decompressor::new(encoding);
+        let mut decompressed = Vec::new();
+        decompressor
+            .write(&compressed[..compressed.len() / 2], &mut decompressed)
+            .unwrap();
+        assert!(data.starts_with(&decompressed));
+        assert_eq!(decompressor.finish(), Err(TokenError::UnexpectedEnd));
+    }
+
+    // The first code references an entry that does not exist.
+    let mut decompressor = Decompressor::new(Encoding::Raw);
+    let mut decompressed = Vec::new();
+    let error = decompressor.write(&[0xFFFF][..], &mut decompressed);
+    assert!(matches!(error, Err(TokenError::InvalidReference(_))));
+    assert_eq!(decompressor.write(&[0][..], &mut decompressed), error);
+    assert_eq!(decompressor.finish(), error);
+
+    // Empty data is valid.
+    assert_eq!(Decompressor::new(Encoding::Base64).finish(), Ok(()));
+}
@@ -0,0 +1,93 @@
+use lz_str::{decompress_partial, Encoding, TokenError};
+
+const DATA: &str =
+    "The quick brown fox jumps over the lazy dog. The quick brown fox jumps over the lazy dog.";
+
+fn compress(data: &str, encoding: Encoding) -> Vec<u16> {
+    match encoding {
+        Encoding::Raw => lz_str::compress(data),
+        Encoding::Utf16 => lz_str::compress_to_utf16(data).encode_utf16().collect(),
+        Encoding::EncodedUriComponent => lz_str::compress_to_encoded_uri_component(data)
+            .encode_utf16()
+            .collect(),
+        Encoding::Base64 => lz_str::compress_to_base64(data).encode_utf16().collect(),
+    }
+}
+
+const ENCODINGS: [Encoding; 4] = [
+    Encoding::Raw,
+    Encoding::Utf16,
+    Encoding::EncodedUriComponent,
+    Encoding::Base64,
+];
+
+#[test]
+fn partial_complete() {
+    let expected: Vec<u16> = DATA.encode_utf16().collect();
+    for encoding in ENCODINGS {
+        let compressed = compress(DATA, encoding);
+        let partial = decompress_partial(&compressed, encoding);
+
+        assert!(partial.is_complete());
+        assert_eq!(partial.output, expected);
+        assert!(partial.position < compressed.len());
+    }
+
+    let partial = decompress_partial("", Encoding::Base64);
+    assert!(partial.is_complete());
+    assert!(partial.output.is_empty());
+}
+
+#[test]
+fn partial_truncated() {
+    let expected: Vec<u16> = DATA.encode_utf16().collect();
+    for encoding in ENCODINGS {
+        let compressed = compress(DATA, encoding);
+        let complete = decompress_partial(&compressed, encoding);
+
+        let mut last_len = 0;
+        for len in 1..=complete.position {
+            let partial = decompress_partial(&compressed[..len], encoding);
+
+            assert_eq!(partial.error, Some(TokenError::UnexpectedEnd));
+            assert!(expected.starts_with(&partial.output));
+            assert!(partial.output.len() >= last_len);
+            assert!(partial.position < len);
+            last_len = partial.output.len();
+        }
+
+        // Most of the data survives losing the last few chars.
+        let partial = decompress_partial(&compressed[..complete.position], encoding);
+        assert!(
+            partial.output.len() * 2 > expected.len(),
+            "{:?} recovered {} chars",
+            encoding,
+            partial.output.len()
+        );
+    }
+}
+
+#[test]
+fn partial_damaged() {
+    // The first code is 3, a reference before any entry exists.
+    let partial = decompress_partial(&[0xC000][..], Encoding::Raw);
+    assert_eq!(partial.error, Some(TokenError::InvalidReference(3)));
+    assert!(partial.output.is_empty());
+    assert_eq!(partial.position, 0);
+
+    // Damage after the start keeps the codes before it,
+    // though it may only be noticed a few codes later.
+    let compressed = compress(DATA, Encoding::Base64);
+    let mut found = false;
+    for i in (compressed.len() / 2)..compressed.len() {
+        let mut damaged = compressed.clone();
+        damaged[i] = u16::from(b'/');
+
+        let partial = decompress_partial(&damaged, Encoding::Base64);
+        if let Some(TokenError::InvalidReference(_)) = partial.error {
+            assert!(!partial.output.is_empty());
+            found = true;
+        }
+    }
+    assert!(found);
+}
@@ -0,0 +1,55 @@
+use lz_str::{Encoding, RandomAccessIndex};
+use rand::Rng;
+
+#[test]
+fn extract_matches_decompress() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..20 {
+        let len = rng.gen_range(0..3000);
+        let data: Vec<u16> = (0..len)
+            .map(|_| {
+                if rng.gen_bool(0.01) {
+                    rng.gen()
+                } else {
+                    rng.gen_range(u16::from(b'a')..u16::from(b'f'))
+                }
+            })
+            .collect();
+
+        let compressed = lz_str::compress_to_encoded_uri_component(&data);
+        let index = RandomAccessIndex::new(&compressed, Encoding::EncodedUriComponent)
+            .expect("valid index");
+        assert_eq!(index.len(), data.len());
+        assert_eq!(index.extract(0..data.len()).as_deref(), Some(&data[..]));
+
+        for _ in 0..100 {
+            let start = rng.gen_range(0..=data.len());
+            let end = rng.gen_range(start..=data.len());
+            assert_eq!(
+                index.extract(start..end).as_deref(),
+                Some(&data[start..end])
+            );
+        }
+    }
+}
+
+#[test]
+fn extract_out_of_bounds() {
+    let compressed = lz_str::compress("aaaaabaaaaacaaaaadaaaaaeaaaaa");
+    let index = RandomAccessIndex::new(&compressed, Encoding::Raw).expect("valid index");
+    assert_eq!(index.len(), 29);
+    assert_eq!(index.extract(29..29), Some(Vec::new()));
+    assert_eq!(index.extract(28..30), None);
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = index.extract(5..4);
+    assert_eq!(reversed, None);
+
+    let index = RandomAccessIndex::new("", Encoding::Raw).expect("valid index");
+    assert!(index.is_empty());
+    assert_eq!(index.extract(0..0), Some(Vec::new()));
+}
+
+#[test]
+fn invalid_index() {
+    assert!(RandomAccessIndex::new("bed123", Encoding::Raw).is_none());
+}
@@ -0,0 +1,96 @@
+use lz_str::{repair, repair_with_validator, Edit, Encoding};
+
+/// The number of chars before the break that are searched.
+const SEARCH_WINDOW: usize = 256;
+
+const DATA: &str = "{\"user\":\"alice\",\"theme\":\"dark\",\"tabs\":[1,2,3,5,8,13]}";
+
+/// Some damage still decompresses, so only a strict validator can catch it.
+fn is_data(output: &[u16]) -> bool {
+    output.iter().copied().eq(DATA.encode_utf16())
+}
+
+#[test]
+fn repair_valid() {
+    let compressed = lz_str::compress_to_base64(DATA);
+    assert!(repair(&compressed, Encoding::Base64).is_empty());
+}
+
+#[test]
+fn repair_substitution() {
+    let compressed: Vec<u16> = lz_str::compress_to_encoded_uri_component(DATA)
+        .encode_utf16()
+        .collect();
+    let expected: Vec<u16> = DATA.encode_utf16().collect();
+
+    for position in [0, compressed.len() / 2, compressed.len() - 1] {
+        let mut damaged = compressed.clone();
+        damaged[position] = if damaged[position] == u16::from(b'A') {
+            u16::from(b'B')
+        } else {
+            u16::from(b'A')
+        };
+
+        let repairs = repair_with_validator(&damaged, Encoding::EncodedUriComponent, is_data);
+        let repair = repairs
+            .iter()
+            .find(|repair| repair.payload == compressed)
+            .expect("missing repair");
+        assert_eq!(repair.output, expected);
+        assert!(repair.canonical);
+        assert_eq!(repair.edit.position(), position);
+    }
+}
+
+#[test]
+fn repair_insertion_and_deletion() {
+    let compressed: Vec<u16> = lz_str::compress_to_base64(DATA).encode_utf16().collect();
+
+    let mut missing = compressed.clone();
+    missing.remove(7);
+    let repairs = repair(&missing, Encoding::Base64);
+    assert!(repairs
+        .iter()
+        .any(|repair| repair.payload == compressed && repair.canonical));
+
+    let mut extra = compressed.clone();
+    extra.insert(7, u16::from(b'Q'));
+    let repairs = repair(&extra, Encoding::Base64);
+    assert!(repairs
+        .iter()
+        .any(|repair| repair.payload == compressed && repair.edit == Edit::Delete { position: 7 }));
+}
+
+#[test]
+fn repair_bit_flip() {
+    let compressed: Vec<u16> = lz_str::compress_to_utf16(DATA).encode_utf16().collect();
+
+    let mut damaged = compressed.clone();
+    damaged[3] ^= 1 << 4;
+    let repairs = repair_with_validator(&damaged, Encoding::Utf16, is_data);
+    assert_eq!(repairs[0].payload, compressed);
+}
+
+#[test]
+fn repair_long_payload() {
+    let data: String = (0..100)
+        .map(|i| format!("{{\"id\":{i},\"seen\":{}}}", i * 7919 % 1000))
+        .collect();
+    let compressed: Vec<u16> = lz_str::compress_to_encoded_uri_component(&data)
+        .encode_utf16()
+        .collect();
+    assert!(compressed.len() > SEARCH_WINDOW);
+
+    // Each repair is tried at most once, near where decompression breaks.
+    let mut damaged = compressed.clone();
+    damaged.remove(compressed.len() - 100);
+    let repairs = repair(&damaged, Encoding::EncodedUriComponent);
+    assert!(repairs
+        .iter()
+        .any(|repair| repair.payload == compressed && repair.canonical));
+
+    let mut payloads: Vec<&Vec<u16>> = repairs.iter().map(|repair| &repair.payload).collect();
+    payloads.sort();
+    payloads.dedup();
+    assert_eq!(payloads.len(), repairs.len());
+}
@@ -0,0 +1,89 @@
+use lz_str::{compressed_contains, compressed_find_all, Encoding};
+use rand::Rng;
+
+fn find_all_naive(data: &[u16], pattern: &[u16]) -> Vec<usize> {
+    if pattern.is_empty() {
+        return Vec::new();
+    }
+
+    data.windows(pattern.len())
+        .enumerate()
+        .filter(|(_, window)| *window == pattern)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[test]
+fn search_matches_naive() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..200 {
+        let alphabet_len = rng.gen_range(1..4);
+        let len = rng.gen_range(0..1000);
+        let data: Vec<u16> = (0..len)
+            .map(|_| u16::from(b'a') + rng.gen_range(0..alphabet_len))
+            .collect();
+        let compressed = lz_str::compress_to_base64(&data);
+
+        for _ in 0..10 {
+            let pattern_len = rng.gen_range(0..12);
+            let pattern: Vec<u16> = if !data.is_empty() && rng.gen_bool(0.5) {
+                let start = rng.gen_range(0..data.len());
+                data[start..(start + pattern_len).min(data.len())].to_vec()
+            } else {
+                (0..pattern_len)
+                    .map(|_| u16::from(b'a') + rng.gen_range(0..alphabet_len))
+                    .collect()
+            };
+
+            let expected = find_all_naive(&data, &pattern);
+            assert_eq!(
+                compressed_find_all(&compressed, Encoding::Base64, &pattern),
+                Some(expected.clone())
+            );
+            assert_eq!(
+                compressed_contains(&compressed, Encoding::Base64, &pattern),
+                Some(pattern.is_empty() || !expected.is_empty())
+            );
+        }
+    }
+}
+
+#[test]
+fn search_text() {
+    let data = "The quick brown fox jumps over the lazy dog. The lazy dog sleeps.";
+    let compressed = lz_str::compress(data);
+    assert_eq!(
+        compressed_find_all(&compressed, Encoding::Raw, "lazy"),
+        Some(vec![35, 49])
+    );
+    assert_eq!(
+        compressed_contains(&compressed, Encoding::Raw, "cat"),
+        Some(false)
+    );
+}
+
+#[test]
+fn search_invalid() {
+    assert_eq!(compressed_contains("bed123", Encoding::Raw, "a"), None);
+    assert_eq!(compressed_find_all("bed123", Encoding::Raw, ""), None);
+}
+
+#[test]
+fn search_empty_pattern() {
+    let compressed = lz_str::compress("aaaa");
+    assert_eq!(
+        compressed_find_all(&compressed, Encoding::Raw, ""),
+        Some(Vec::new())
+    );
+    assert_eq!(
+        compressed_contains(&compressed, Encoding::Raw, ""),
+        Some(true)
+    );
+
+    // Claims to decompress to far more than could be listed.
+    let huge = lz_str::compress(vec![u16::from(b'a'); 1 << 20]);
+    assert_eq!(
+        compressed_find_all(&huge, Encoding::Raw, ""),
+        Some(Vec::new())
+    );
+}
@@ -0,0 +1,56 @@
+use lz_str::{Compressor, Encoding};
+use rand::{Rng, SeedableRng};
+
+/// Make inputs with lengths around the largest input that is compressed on the stack.
+fn inputs(rng: &mut impl Rng) -> Vec<Vec<u16>> {
+    [0, 1, 16, 63, 64, 65, 255, 256, 257, 1000, 1023, 1024, 1025, 2048]
+        .iter()
+        .flat_map(|&len| {
+            let text: Vec<u16> = (0..len).map(|_| rng.gen_range(b'a'..b'e').into()).collect();
+            let random: Vec<u16> = (0..len).map(|_| rng.gen()).collect();
+            // Decompresses to much more than it compresses from.
+            let repeated = vec![u16::from(b'a'); len * 8];
+            vec![text, random, repeated]
+        })
+        .collect()
+}
+
+#[test]
+fn small_inputs_round_trip() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(49);
+    for input in inputs(&mut rng) {
+        // The incremental compressor never uses the stack-only path.
+        let mut expected = Vec::new();
+        let mut compressor = Compressor::new(Encoding::Raw);
+        compressor.write(&input, &mut expected);
+        compressor.finish(&mut expected);
+
+        let compressed = lz_str::compress(&input);
+        assert_eq!(compressed, expected);
+        assert_eq!(lz_str::decompress(&compressed).as_ref(), Some(&input));
+
+        let compressed = lz_str::compress_to_utf16(&input);
+        assert_eq!(
+            lz_str::decompress_from_utf16(&compressed).as_ref(),
+            Some(&input)
+        );
+
+        let compressed = lz_str::compress_to_encoded_uri_component(&input);
+        assert_eq!(
+            lz_str::decompress_from_encoded_uri_component(&compressed).as_ref(),
+            Some(&input)
+        );
+
+        let compressed = lz_str::compress_to_base64(&input);
+        assert_eq!(
+            lz_str::decompress_from_base64(&compressed).as_ref(),
+            Some(&input)
+        );
+
+        let compressed = lz_str::compress_to_uint8_array(&input);
+        assert_eq!(
+            lz_str::decompress_from_uint8_array(&compressed).as_ref(),
+            Some(&input)
+        );
+    }
+}
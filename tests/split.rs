@@ -0,0 +1,106 @@
+use lz_str::{split_payload, Encoding, ReassembleError, Reassembler};
+
+fn payload(encoding: Encoding) -> String {
+    let data = "{\"state\":[1,2,3],\"name\":\"The quick brown fox\"} ".repeat(50);
+    match encoding {
+        Encoding::Base64 => lz_str::compress_to_base64(&data),
+        _ => lz_str::compress_to_encoded_uri_component(&data),
+    }
+}
+
+#[test]
+fn split_round_trip() {
+    for encoding in [Encoding::EncodedUriComponent, Encoding::Base64] {
+        let payload = payload(encoding);
+        for max_len in [11, 50, 1000, 10_000] {
+            let parts = split_payload(&payload, encoding, max_len).expect("cannot split");
+            assert!(parts.iter().all(|part| part.len() <= max_len));
+
+            // Parts only use the alphabet of the payload.
+            for part in parts.iter() {
+                assert!(part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+-$/=".contains(c)));
+            }
+
+            let mut reassembler = Reassembler::new(encoding);
+            for part in parts
+                .iter()
+                .step_by(2)
+                .chain(parts.iter().skip(1).step_by(2))
+            {
+                reassembler.push(part).expect("invalid part");
+            }
+            assert!(reassembler.is_complete());
+            assert_eq!(reassembler.count(), Some(parts.len()));
+            assert_eq!(reassembler.finish(), Ok(payload.clone()));
+        }
+    }
+}
+
+#[test]
+fn split_invalid() {
+    let payload = payload(Encoding::Base64);
+    assert_eq!(split_payload(&payload, Encoding::Utf16, 100), None);
+    assert_eq!(split_payload(&payload, Encoding::Base64, 10), None);
+
+    let parts = split_payload("", Encoding::Base64, 20).expect("cannot split");
+    assert_eq!(parts.len(), 1);
+}
+
+#[test]
+fn reassemble_missing() {
+    let encoding = Encoding::EncodedUriComponent;
+    let payload = payload(encoding);
+    let parts = split_payload(&payload, encoding, 40).expect("cannot split");
+
+    let mut reassembler = Reassembler::new(encoding);
+    assert_eq!(reassembler.missing(), Vec::<usize>::new());
+    assert_eq!(reassembler.finish(), Err(ReassembleError::Missing(vec![0])));
+
+    for (index, part) in parts.iter().enumerate() {
+        if index != 1 && index != 4 {
+            reassembler.push(part).expect("invalid part");
+        }
+    }
+    assert!(!reassembler.is_complete());
+    assert_eq!(reassembler.missing(), [1, 4]);
+    assert_eq!(
+        reassembler.finish(),
+        Err(ReassembleError::Missing(vec![1, 4]))
+    );
+}
+
+#[test]
+fn reassemble_errors() {
+    let encoding = Encoding::EncodedUriComponent;
+    let parts = split_payload(&payload(encoding), encoding, 40).expect("cannot split");
+    let other = split_payload(
+        &lz_str::compress_to_encoded_uri_component("other"),
+        encoding,
+        40,
+    )
+    .expect("cannot split");
+
+    let mut reassembler = Reassembler::new(encoding);
+    assert_eq!(
+        reassembler.push("short"),
+        Err(ReassembleError::InvalidHeader)
+    );
+    assert_eq!(
+        reassembler.push("!!!!!!!!!!abc"),
+        Err(ReassembleError::InvalidHeader)
+    );
+
+    reassembler.push(&parts[0]).expect("invalid part");
+    assert_eq!(reassembler.push(&other[0]), Err(ReassembleError::Mismatch));
+
+    // Damage the data of a part.
+    for part in parts.iter().skip(1) {
+        let mut part = part.clone();
+        let last = part.pop();
+        part.push(if last == Some('0') { '1' } else { '0' });
+        reassembler.push(&part).expect("invalid part");
+    }
+    assert_eq!(reassembler.finish(), Err(ReassembleError::ChecksumMismatch));
+}
@@ -0,0 +1,99 @@
+use lz_str::{decompress_prefix, decompress_streams, decompress_strict, Encoding, TokenError};
+
+const INPUTS: [&str; 4] = [
+    "",
+    "a",
+    "The quick brown fox jumps over the lazy dog",
+    "\u{2603}\u{1F600} snowman",
+];
+
+fn compress(data: &str, encoding: Encoding) -> Vec<u16> {
+    match encoding {
+        Encoding::Raw => lz_str::compress(data),
+        Encoding::Utf16 => lz_str::compress_to_utf16(data).encode_utf16().collect(),
+        Encoding::EncodedUriComponent => lz_str::compress_to_encoded_uri_component(data)
+            .encode_utf16()
+            .collect(),
+        Encoding::Base64 => lz_str::compress_to_base64(data).encode_utf16().collect(),
+    }
+}
+
+const ENCODINGS: [Encoding; 4] = [
+    Encoding::Raw,
+    Encoding::Utf16,
+    Encoding::EncodedUriComponent,
+    Encoding::Base64,
+];
+
+#[test]
+fn prefix_consumed() {
+    for encoding in ENCODINGS {
+        for input in INPUTS {
+            let expected: Vec<u16> = input.encode_utf16().collect();
+            let compressed = compress(input, encoding);
+
+            assert_eq!(
+                decompress_prefix(&compressed, encoding),
+                Some((expected.clone(), compressed.len()))
+            );
+            assert_eq!(
+                decompress_strict(&compressed, encoding),
+                Some(expected.clone())
+            );
+
+            let mut junk = compressed.clone();
+            junk.extend("AAAA".encode_utf16());
+            assert_eq!(
+                decompress_prefix(&junk, encoding),
+                Some((expected, compressed.len()))
+            );
+            assert_eq!(decompress_strict(&junk, encoding), None);
+        }
+    }
+}
+
+#[test]
+fn strict_accepts_other_padding() {
+    // The reference implementation adds three '=' where this one adds four.
+    let compressed = lz_str::compress_to_base64("a");
+    let trimmed = format!("{}===", compressed.trim_end_matches('='));
+    assert!(decompress_strict(&trimmed, Encoding::Base64).is_some());
+
+    // Padding is optional.
+    let trimmed = compressed.trim_end_matches('=');
+    assert!(decompress_strict(trimmed, Encoding::Base64).is_some());
+}
+
+#[test]
+fn concatenated_streams() {
+    for encoding in ENCODINGS {
+        let mut compressed = Vec::new();
+        let mut ends = Vec::new();
+        for input in INPUTS.iter().skip(1) {
+            compressed.extend(compress(input, encoding));
+            ends.push(compressed.len());
+        }
+
+        let mut streams = decompress_streams(&compressed, encoding);
+        for (input, end) in INPUTS.iter().skip(1).zip(ends) {
+            let expected: Vec<u16> = input.encode_utf16().collect();
+            assert_eq!(streams.next(), Some(Ok(expected)));
+            assert_eq!(streams.position(), end);
+        }
+        assert_eq!(streams.next(), None);
+    }
+}
+
+#[test]
+fn concatenated_streams_error() {
+    let mut compressed = lz_str::compress_to_base64("first");
+    compressed.push_str("zed123");
+
+    let mut streams = decompress_streams(&compressed, Encoding::Base64);
+    assert!(matches!(streams.next(), Some(Ok(_))));
+    assert!(matches!(
+        streams.next(),
+        Some(Err(TokenError::UnexpectedEnd)) | Some(Err(TokenError::InvalidReference(_)))
+    ));
+    assert_eq!(streams.next(), None);
+}
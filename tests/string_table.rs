@@ -0,0 +1,75 @@
+use lz_str::{StringTable, StringTableBuilder};
+
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().collect()
+}
+
+#[test]
+fn string_table_round_trip() {
+    let mut builder = StringTableBuilder::new();
+    builder.block_len(200);
+
+    let mut expected = Vec::new();
+    for i in 0..100 {
+        let key = format!("strings.item_{i}");
+        let value = format!("This is the localized text for item {i}, with a snowman \u{2603}.");
+        builder.insert(&key, value.as_str());
+        expected.push((key, value));
+    }
+    builder.insert("empty", "");
+    builder.insert("weird,key,\u{1F600}", "value");
+    let table = builder.build();
+
+    let reader = StringTable::new(&table).expect("invalid table");
+    assert_eq!(reader.len(), 102);
+    for (key, value) in expected.iter() {
+        assert_eq!(reader.get(key), Some(wide(value)));
+    }
+    assert_eq!(reader.get("empty"), Some(Vec::new()));
+    assert_eq!(reader.get("weird,key,\u{1F600}"), Some(wide("value")));
+    assert_eq!(reader.get("missing"), None);
+
+    let keys = [
+        "strings.item_3",
+        "missing",
+        "strings.item_99",
+        "strings.item_4",
+    ];
+    let values = reader.get_all(&keys);
+    assert_eq!(values[0], Some(wide(&expected[3].1)));
+    assert_eq!(values[1], None);
+    assert_eq!(values[2], Some(wide(&expected[99].1)));
+    assert_eq!(values[3], Some(wide(&expected[4].1)));
+
+    // Compressing the strings together beats compressing them one by one.
+    let separate: usize = expected
+        .iter()
+        .map(|(_, value)| lz_str::compress_to_base64(value.as_str()).len())
+        .sum();
+    assert!(table.len() < separate);
+}
+
+#[test]
+fn string_table_replace() {
+    let mut builder = StringTableBuilder::new();
+    builder
+        .insert("a", "first")
+        .insert("b", "second")
+        .insert("a", "third");
+
+    let table = builder.build();
+    let reader = StringTable::new(&table).expect("invalid table");
+    assert_eq!(reader.len(), 2);
+    assert_eq!(reader.get("a"), Some(wide("third")));
+}
+
+#[test]
+fn string_table_empty_and_invalid() {
+    let table = StringTableBuilder::new().build();
+    let reader = StringTable::new(&table).expect("invalid table");
+    assert!(reader.is_empty());
+
+    assert!(StringTable::new("").is_none());
+    assert!(StringTable::new("LZST2\n").is_none());
+    assert!(StringTable::new("LZST1\nzzz").is_none());
+}
@@ -0,0 +1,92 @@
+use lz_str::{compress, compress_internal, encode_tokens, tokenize, Token, TokenError};
+
+const TEST_STR: &str = "The quick brown fox jumps over the lazy dog";
+
+#[test]
+fn tokenize_golden() {
+    let compressed = compress("aaaaab");
+    let tokens: Vec<Token> = tokenize(&compressed, 16)
+        .collect::<Result<_, _>>()
+        .expect("valid tokens");
+    assert_eq!(
+        tokens,
+        [
+            Token::U8(b'a'),
+            // "aa" is the entry that is about to be created.
+            Token::Reference(4),
+            Token::Reference(4),
+            Token::U8(b'b'),
+            Token::Close,
+        ]
+    );
+
+    let compressed = compress("\u{2603}\u{2603}");
+    let tokens: Vec<Token> = tokenize(&compressed, 16)
+        .collect::<Result<_, _>>()
+        .expect("valid tokens");
+    assert_eq!(
+        tokens,
+        [Token::U16(0x2603), Token::Reference(3), Token::Close]
+    );
+}
+
+#[test]
+fn tokens_round_trip() {
+    let long: String = (0..1000).map(|i| format!("{} ", i % 37)).collect();
+    for data in [TEST_STR, "", "a", "aaaaabaaaaacaaaaadaaaaaeaaaaa", &long] {
+        let data: Vec<u16> = data.encode_utf16().collect();
+        for (bits_per_char, to_char, from_char) in [
+            (16, (|n| n) as fn(u16) -> u16, (|n| n) as fn(u16) -> u16),
+            (15, |n| n + 32, |n| n - 32),
+            (6, |n| n, |n| n),
+        ] {
+            let compressed = compress_internal(&data, bits_per_char, to_char);
+            let tokens: Vec<Token> = tokenize(
+                compressed.iter().map(|&c| from_char(c)).collect::<Vec<_>>(),
+                bits_per_char,
+            )
+            .collect::<Result<_, _>>()
+            .expect("valid tokens");
+            assert_eq!(tokens.last(), Some(&Token::Close));
+
+            let encoded = encode_tokens(tokens, bits_per_char, to_char).expect("valid tokens");
+            assert_eq!(encoded, compressed);
+        }
+    }
+}
+
+#[test]
+fn encode_tokens_adds_close() {
+    let tokens = [Token::U8(b'a'), Token::U8(b'b'), Token::Reference(5)];
+    let encoded = encode_tokens(tokens, 16, std::convert::identity).expect("valid tokens");
+    assert_eq!(encoded, compress("abab"));
+}
+
+#[test]
+fn encode_tokens_invalid_reference() {
+    assert_eq!(
+        encode_tokens([Token::Reference(3)], 16, std::convert::identity),
+        Err(TokenError::InvalidReference(3))
+    );
+    assert_eq!(
+        encode_tokens(
+            [Token::U8(b'a'), Token::Reference(5)],
+            16,
+            std::convert::identity
+        ),
+        Err(TokenError::InvalidReference(5))
+    );
+}
+
+#[test]
+fn tokenize_errors() {
+    assert_eq!(tokenize(&[][..], 16).next(), None);
+
+    let compressed = compress(TEST_STR);
+    let truncated = &compressed[..compressed.len() / 2];
+    let last = tokenize(truncated, 16).last().expect("at least one token");
+    assert_eq!(last, Err(TokenError::UnexpectedEnd));
+
+    let invalid: Vec<u16> = "bed123".encode_utf16().collect();
+    assert!(tokenize(&invalid, 16).any(|token| token.is_err()));
+}
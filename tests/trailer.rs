@@ -0,0 +1,91 @@
+use lz_str::{compress_with_trailer, decompress_with_trailer, Encoding, Trailer, TrailerError};
+use std::convert::TryFrom;
+
+const ENCODINGS: [Encoding; 4] = [
+    Encoding::Raw,
+    Encoding::Utf16,
+    Encoding::EncodedUriComponent,
+    Encoding::Base64,
+];
+
+fn decompress(compressed: &[u16], encoding: Encoding) -> Option<Vec<u16>> {
+    let compressed = String::from_utf16_lossy(compressed);
+    match encoding {
+        Encoding::Raw => unreachable!(),
+        Encoding::Utf16 => lz_str::decompress_from_utf16(&compressed),
+        Encoding::EncodedUriComponent => lz_str::decompress_from_encoded_uri_component(&compressed),
+        Encoding::Base64 => lz_str::decompress_from_base64(&compressed),
+    }
+}
+
+#[test]
+fn trailer_round_trip() {
+    for data in [
+        "",
+        "a",
+        "The quick brown fox jumps over the lazy dog",
+        "\u{2603}\u{1F600}",
+    ] {
+        let expected: Vec<u16> = data.encode_utf16().collect();
+        for encoding in ENCODINGS {
+            let compressed = compress_with_trailer(data, encoding, 7);
+
+            let (decompressed, trailer) =
+                decompress_with_trailer(&compressed, encoding).expect("invalid data");
+            assert_eq!(decompressed, expected);
+            assert_eq!(trailer, Some(Trailer::new(&expected, 7)));
+            assert_eq!(
+                trailer.map(|trailer| trailer.len),
+                Some(u32::try_from(expected.len()).unwrap())
+            );
+
+            // Decompressors that do not know about the trailer ignore it.
+            let legacy = match encoding {
+                Encoding::Raw => lz_str::decompress(&compressed),
+                _ => decompress(&compressed, encoding),
+            };
+            assert_eq!(legacy, Some(expected.clone()));
+        }
+    }
+}
+
+#[test]
+fn trailer_missing() {
+    let data = "The quick brown fox jumps over the lazy dog";
+    let expected: Vec<u16> = data.encode_utf16().collect();
+
+    let compressed = lz_str::compress_to_base64(data);
+    assert_eq!(
+        decompress_with_trailer(&compressed, Encoding::Base64),
+        Ok((expected.clone(), None))
+    );
+
+    let compressed = lz_str::compress_to_utf16(data);
+    assert_eq!(
+        decompress_with_trailer(&compressed, Encoding::Utf16),
+        Ok((expected, None))
+    );
+
+    assert_eq!(
+        decompress_with_trailer("", Encoding::Base64),
+        Ok((Vec::new(), None))
+    );
+}
+
+#[test]
+fn trailer_mismatch() {
+    let compressed = compress_with_trailer("The quick brown fox", Encoding::Base64, 1);
+    let trailer = compress_with_trailer("The quick brown cat", Encoding::Base64, 1);
+
+    // Swap in the trailer of different data.
+    let data_len = lz_str::compress_to_base64("The quick brown fox")
+        .trim_end_matches('=')
+        .len();
+    let mut tampered = compressed[..data_len].to_vec();
+    tampered.extend(&trailer[data_len..]);
+
+    assert!(matches!(
+        decompress_with_trailer(&tampered, Encoding::Base64),
+        Err(TrailerError::Mismatch(_))
+    ));
+}